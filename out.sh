@@ -10,7 +10,8 @@ echo "impl AppBuilder for Application {" >> include.rs
 echo "type PathRouter = impl routing::PathRouter;" >> include.rs
 echo "fn build_app(self) -> picoserve::Router<Self::PathRouter> {" >> include.rs
 
-jq -r '
+# Files from public/ are served from the root, like the service worker and the manifest
+jq -r --argjson public "$(ls public | jq -R . | jq -s .)" '
   .assets.assets 
   | map(.[0]) 
   | {
@@ -25,7 +26,7 @@ jq -r '
   | "static_routes!(
     \"\(.base)\",
     \"index.html\",
-    \"assets/\(.css)\",
+\($public | map("    \"\(.)\",\n") | add // "")    \"assets/\(.css)\",
     \"assets/\(.js)\",
     \"assets/\(.wasm)\"
 )"' /home/kyle/coding/controller-ui/target/dx/controller-ui/release/web/.manifest.json >> include.rs
@@ -0,0 +1,61 @@
+// Serves the controller's shell from cache so it opens instantly, even over a flaky robot AP.
+//
+// Bundled assets have content hashes in their names, so a cached copy never goes stale. The
+// page itself is answered from cache and refreshed in the background. Commands and other
+// robot endpoints are never cached.
+
+const CACHE = "controller-ui-v1";
+const SHELL = ["/", "/manifest.json", "/icon-192.png", "/icon-512.png"];
+
+self.addEventListener("install", (event) => {
+    event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll(SHELL)));
+    self.skipWaiting();
+});
+
+self.addEventListener("activate", (event) => {
+    event.waitUntil(
+        caches
+            .keys()
+            .then((keys) => Promise.all(keys.filter((x) => x !== CACHE).map((x) => caches.delete(x))))
+            .then(() => self.clients.claim()),
+    );
+});
+
+async function cacheFirst(request) {
+    const cache = await caches.open(CACHE);
+    const cached = await cache.match(request);
+    if (cached) {
+        return cached;
+    }
+    const response = await fetch(request);
+    if (response.ok) {
+        cache.put(request, response.clone());
+    }
+    return response;
+}
+
+async function staleWhileRevalidate(request) {
+    const cache = await caches.open(CACHE);
+    const cached = await cache.match(request);
+    const fresh = fetch(request)
+        .then((response) => {
+            if (response.ok) {
+                cache.put(request, response.clone());
+            }
+            return response;
+        })
+        .catch(() => cached);
+    return cached || fresh;
+}
+
+self.addEventListener("fetch", (event) => {
+    const url = new URL(event.request.url);
+    if (event.request.method !== "GET" || url.origin !== self.location.origin) {
+        return;
+    }
+    if (event.request.mode === "navigate") {
+        event.respondWith(staleWhileRevalidate(new Request("/")));
+    } else if (url.pathname.startsWith("/assets/") || SHELL.includes(url.pathname)) {
+        event.respondWith(cacheFirst(event.request));
+    }
+});
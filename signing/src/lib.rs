@@ -0,0 +1,252 @@
+//! Signing of controller commands with a pre-shared key, so replayed or tampered requests can
+//! be told apart from the real thing.
+//!
+//! The signature is HMAC-SHA256 over the big-endian sequence number, the big-endian timestamp
+//! in milliseconds since the Unix epoch, and the request body, in that order. Both ends share
+//! this crate; it needs neither `std` nor an allocator, so it builds for the firmware too.
+//!
+//! The UI sends the three alongside the body as `X-Sequence`, `X-Timestamp` and
+//! `X-Signature` (lowercase hex). The robot checks them with [`ReplayGuard::check`].
+
+#![no_std]
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub const SIGNATURE_LEN: usize = 32;
+
+pub type Signature = [u8; SIGNATURE_LEN];
+
+fn mac(key: &[u8], sequence: u64, timestamp_ms: u64, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&sequence.to_be_bytes());
+    mac.update(&timestamp_ms.to_be_bytes());
+    mac.update(body);
+    mac
+}
+
+pub fn sign(key: &[u8], sequence: u64, timestamp_ms: u64, body: &[u8]) -> Signature {
+    mac(key, sequence, timestamp_ms, body)
+        .finalize()
+        .into_bytes()
+        .into()
+}
+
+/// Checks `signature` in constant time.
+pub fn verify(key: &[u8], sequence: u64, timestamp_ms: u64, body: &[u8], signature: &[u8]) -> bool {
+    mac(key, sequence, timestamp_ms, body)
+        .verify_slice(signature)
+        .is_ok()
+}
+
+pub fn to_hex(signature: &Signature) -> [u8; 2 * SIGNATURE_LEN] {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = [0; 2 * SIGNATURE_LEN];
+    for (i, byte) in signature.iter().enumerate() {
+        hex[2 * i] = DIGITS[usize::from(byte >> 4)];
+        hex[2 * i + 1] = DIGITS[usize::from(byte & 0xf)];
+    }
+    hex
+}
+
+pub fn from_hex(hex: &[u8]) -> Option<Signature> {
+    fn nibble(x: u8) -> Option<u8> {
+        match x {
+            b'0'..=b'9' => Some(x - b'0'),
+            b'a'..=b'f' => Some(x - b'a' + 10),
+            b'A'..=b'F' => Some(x - b'A' + 10),
+            _ => None,
+        }
+    }
+    if hex.len() != 2 * SIGNATURE_LEN {
+        return None;
+    }
+    let mut signature = [0; SIGNATURE_LEN];
+    for (byte, pair) in signature.iter_mut().zip(hex.chunks_exact(2)) {
+        *byte = nibble(pair[0])? << 4 | nibble(pair[1])?;
+    }
+    Some(signature)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rejection {
+    BadSignature,
+    /// The sequence number was accepted before, or is too far behind the highest one to tell.
+    Replayed,
+    /// The timestamp is further from the robot's clock than allowed.
+    Stale,
+}
+
+/// How many sequence numbers below the highest one accepted may still arrive late.
+pub const REPLAY_WINDOW: u64 = 64;
+
+/// The robot's side: accepts each correctly signed request at most once, and only while fresh.
+///
+/// Requests may overtake each other on the way, so sequence numbers up to [`REPLAY_WINDOW`]
+/// below the highest one accepted are still taken, once each.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ReplayGuard {
+    highest: Option<u64>,
+    /// Bit `i` is set if `highest - i` was accepted.
+    seen: u64,
+    max_skew_ms: u64,
+}
+
+impl ReplayGuard {
+    pub const fn new(max_skew_ms: u64) -> Self {
+        Self {
+            highest: None,
+            seen: 0,
+            max_skew_ms,
+        }
+    }
+
+    /// How far `sequence` is behind the highest accepted, if it's not ahead of it.
+    fn behind(&self, sequence: u64) -> Option<u64> {
+        self.highest
+            .and_then(|highest| highest.checked_sub(sequence))
+    }
+
+    fn seen(&self, sequence: u64) -> bool {
+        match self.behind(sequence) {
+            Some(behind) => behind >= REPLAY_WINDOW || self.seen & 1 << behind != 0,
+            None => false,
+        }
+    }
+
+    fn remember(&mut self, sequence: u64) {
+        match (self.highest, self.behind(sequence)) {
+            (Some(_), Some(behind)) => self.seen |= 1 << behind,
+            (Some(highest), None) => {
+                let ahead = u32::try_from(sequence - highest).unwrap_or(u32::MAX);
+                self.seen = self.seen.checked_shl(ahead).unwrap_or(0) | 1;
+                self.highest = Some(sequence);
+            }
+            (None, _) => {
+                self.seen = 1;
+                self.highest = Some(sequence);
+            }
+        }
+    }
+
+    /// Checks a request received at `now_ms`, remembering its sequence number if accepted.
+    pub fn check(
+        &mut self,
+        key: &[u8],
+        sequence: u64,
+        timestamp_ms: u64,
+        body: &[u8],
+        signature: &[u8],
+        now_ms: u64,
+    ) -> Result<(), Rejection> {
+        if !verify(key, sequence, timestamp_ms, body, signature) {
+            return Err(Rejection::BadSignature);
+        }
+        if self.seen(sequence) {
+            return Err(Rejection::Replayed);
+        }
+        if now_ms.abs_diff(timestamp_ms) > self.max_skew_ms {
+            return Err(Rejection::Stale);
+        }
+        self.remember(sequence);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"competition key";
+    const BODY: &[u8] = b"cmd=go_front&status=pressed";
+
+    #[test]
+    fn matches_rfc_4231_test_case_2() {
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"Jefe").unwrap();
+        mac.update(b"what do ya want for nothing?");
+        let signature: Signature = mac.finalize().into_bytes().into();
+        assert_eq!(
+            &to_hex(&signature),
+            b"5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn signs_command_vector() {
+        assert_eq!(
+            &to_hex(&sign(KEY, 1, 1_700_000_000_000, BODY)),
+            b"6b9756fba2368a7528c2c1595149740ebc89976ce035927f5e2864868f5bfafa"
+        );
+        assert_eq!(
+            &to_hex(&sign(
+                KEY,
+                2,
+                1_700_000_000_250,
+                b"cmd=blink_rate&status=100"
+            )),
+            b"0153f4495eb7e6a09d48476813bdce703e757fa22248737c394be73723151520"
+        );
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        let signature = sign(KEY, 1, 0, BODY);
+        assert_eq!(from_hex(&to_hex(&signature)), Some(signature));
+        assert_eq!(from_hex(b"abc"), None);
+    }
+
+    #[test]
+    fn accepts_each_request_once() {
+        let mut guard = ReplayGuard::new(5_000);
+        let signature = sign(KEY, 7, 1_000, BODY);
+        assert_eq!(guard.check(KEY, 7, 1_000, BODY, &signature, 1_200), Ok(()));
+        assert_eq!(
+            guard.check(KEY, 7, 1_000, BODY, &signature, 1_300),
+            Err(Rejection::Replayed)
+        );
+    }
+
+    #[test]
+    fn accepts_requests_arriving_out_of_order() {
+        let mut guard = ReplayGuard::new(5_000);
+        let check = |guard: &mut ReplayGuard, sequence| {
+            let signature = sign(KEY, sequence, 1_000, BODY);
+            guard.check(KEY, sequence, 1_000, BODY, &signature, 1_000)
+        };
+        // A release overtaken by the press before it still counts
+        assert_eq!(check(&mut guard, 11), Ok(()));
+        assert_eq!(check(&mut guard, 10), Ok(()));
+        assert_eq!(check(&mut guard, 10), Err(Rejection::Replayed));
+        assert_eq!(check(&mut guard, 11), Err(Rejection::Replayed));
+        assert_eq!(check(&mut guard, 80), Ok(()));
+        assert_eq!(check(&mut guard, 17), Ok(()));
+        assert_eq!(check(&mut guard, 16), Err(Rejection::Replayed));
+        assert_eq!(check(&mut guard, 500), Ok(()));
+        assert_eq!(check(&mut guard, 80), Err(Rejection::Replayed));
+    }
+
+    #[test]
+    fn rejects_tampering_and_stale_requests() {
+        let mut guard = ReplayGuard::new(5_000);
+        let signature = sign(KEY, 1, 1_000, BODY);
+        assert_eq!(
+            guard.check(
+                KEY,
+                1,
+                1_000,
+                b"cmd=go_back&status=pressed",
+                &signature,
+                1_000
+            ),
+            Err(Rejection::BadSignature)
+        );
+        assert_eq!(
+            guard.check(b"other key", 1, 1_000, BODY, &signature, 1_000),
+            Err(Rejection::BadSignature)
+        );
+        assert_eq!(
+            guard.check(KEY, 1, 1_000, BODY, &signature, 10_000),
+            Err(Rejection::Stale)
+        );
+    }
+}
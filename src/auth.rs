@@ -0,0 +1,319 @@
+//! Pairing with the robot, so only devices that know its PIN can drive it.
+//!
+//! The robot shows or prints a PIN; `POST /pair` with `{"pin": ...}` trades it for a session
+//! token, which is kept on this device per robot and sent as a bearer token with every
+//! command. Robots answer `401` with an [`AuthError`] body when the token is missing or no
+//! longer valid. Robots without pairing never do, and are driven as before.
+//!
+//! On shared networks a bearer token can be sniffed and replayed, so commands can also be
+//! signed with a pre-shared key through [`command_signing`].
+
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use dioxus::prelude::*;
+use reqwasm::http::{Request, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::{ROBOT, command_log, storage};
+
+#[derive(Serialize)]
+struct PairRequest<'a> {
+    pin: &'a str,
+}
+
+#[derive(Deserialize)]
+struct PairResponse {
+    token: String,
+}
+
+/// Why the robot refused a request, as sent in the `error` field of a `401` or `429` body.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthError {
+    InvalidPin,
+    TooManyAttempts,
+    MissingToken,
+    InvalidToken,
+    /// The signature doesn't match the body, or the robot expects a signed request.
+    BadSignature,
+    /// The sequence number was already used.
+    Replayed,
+    /// The timestamp is too far from the robot's clock.
+    Stale,
+}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::InvalidPin => "wrong PIN",
+            Self::TooManyAttempts => "too many attempts, try again later",
+            Self::MissingToken => "this device isn't paired",
+            Self::InvalidToken => "pairing expired, pair again",
+            Self::BadSignature => "signature rejected, check the signing key",
+            Self::Replayed => "rejected as a replay",
+            Self::Stale => "timestamp rejected, check this device's clock",
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: AuthError,
+}
+
+/// The session token for the selected robot, if this device is paired with it.
+static TOKEN: GlobalSignal<Option<String>> = Signal::global(|| None);
+/// Set once the selected robot turns a request away for lack of pairing.
+static REQUIRED: GlobalSignal<bool> = Signal::global(|| false);
+/// The robot whose token and signing key, if any, have been loaded.
+static LOADED: GlobalSignal<Option<String>> = Signal::global(|| None);
+
+/// The pre-shared key commands to the selected robot are signed with, if any.
+static SIGNING_KEY: GlobalSignal<Option<String>> = Signal::global(|| None);
+/// The last sequence number signed.
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Tokens are kept per robot, since each robot issues its own.
+fn storage_key() -> String {
+    format!("token/{}", ROBOT.peek().name)
+}
+
+fn signing_storage_key() -> String {
+    format!("signing_key/{}", ROBOT.peek().name)
+}
+
+/// Signs a request with `body` if a signing key is set.
+pub fn sign(request: Request, body: &str) -> Request {
+    let Some(key) = &*SIGNING_KEY.peek() else {
+        return request;
+    };
+    let timestamp_ms = command_log::unix_millis();
+    // Counting from the clock keeps sequence numbers rising across reloads of the page
+    let floor = timestamp_ms * 1000;
+    let previous = SEQUENCE
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| {
+            Some(x.max(floor) + 1)
+        })
+        .unwrap_or_else(|x| x);
+    let sequence = previous.max(floor) + 1;
+    let signature = command_signing::sign(key.as_bytes(), sequence, timestamp_ms, body.as_bytes());
+    let signature = command_signing::to_hex(&signature);
+    request
+        .header("X-Sequence", &sequence.to_string())
+        .header("X-Timestamp", &timestamp_ms.to_string())
+        .header(
+            "X-Signature",
+            std::str::from_utf8(&signature).expect("hex digits are ASCII"),
+        )
+}
+
+/// Whether the selected robot's token and signing key have loaded, so requests can be made.
+/// Requests made before would be refused, and the refusal would forget the token.
+pub fn loaded() -> bool {
+    LOADED.read().as_ref() == Some(&ROBOT.read().name)
+}
+
+/// Attaches the session token, if any, to a request for the robot.
+pub fn authorize(request: Request) -> Request {
+    match &*TOKEN.peek() {
+        Some(token) => request.header("Authorization", &format!("Bearer {token}")),
+        None => request,
+    }
+}
+
+/// Reads the reason out of an auth failure, forgetting the token if the robot no longer
+/// accepts it. Returns `None` for responses that aren't about pairing.
+pub async fn check(resp: &Response) -> Option<AuthError> {
+    if !matches!(resp.status(), 401 | 429) {
+        return None;
+    }
+    let error = resp.json::<ErrorBody>().await.ok()?.error;
+    if matches!(error, AuthError::MissingToken | AuthError::InvalidToken) {
+        forget();
+        *REQUIRED.write() = true;
+    }
+    Some(error)
+}
+
+async fn pair(pin: &str) -> Result<(), String> {
+    let body = serde_json::to_string(&PairRequest { pin }).map_err(|err| err.to_string())?;
+    let resp = Request::post(&format!("{}/pair", ROBOT.peek().base_url))
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+    if let Some(error) = check(&resp).await {
+        return Err(error.to_string());
+    }
+    if resp.status() != 200 {
+        return Err(format!("the robot answered {}", resp.status()));
+    }
+    let PairResponse { token } = resp.json().await.map_err(|err| err.to_string())?;
+    storage::save(&storage_key(), &token);
+    *TOKEN.write() = Some(token);
+    *REQUIRED.write() = false;
+    Ok(())
+}
+
+fn forget() {
+    storage::remove(&storage_key());
+    *TOKEN.write() = None;
+}
+
+/// Forgets the token, telling the robot to revoke it if it can be reached.
+async fn unpair() {
+    let request = authorize(Request::post(&format!("{}/unpair", ROBOT.peek().base_url)));
+    forget();
+    if let Err(err) = request.send().await {
+        warn!("The robot wasn't told about unpairing: {err}");
+    }
+}
+
+/// Switches to the selected robot's token and signing key whenever the robot changes.
+pub fn use_pairing() {
+    use_effect(|| {
+        // Subscribes the effect to robot changes; `storage_key` only peeks.
+        let robot = ROBOT.read().name.clone();
+        *REQUIRED.write() = false;
+        spawn(async move {
+            *TOKEN.write() = storage::load(&storage_key()).await;
+            *SIGNING_KEY.write() = storage::load(&signing_storage_key()).await;
+            *LOADED.write() = Some(robot);
+        });
+    });
+}
+
+// --- COMPONENT: Pairing reminder for the header ---
+#[component]
+pub fn PairingBadge() -> Element {
+    if !REQUIRED() {
+        return VNode::empty();
+    }
+
+    rsx! {
+        span {
+            class: "px-4 py-3 rounded-xl glass-panel text-sm font-bold text-yellow-300",
+            title: "The robot ignores commands until this device is paired in Settings",
+            "🔒 Not paired"
+        }
+    }
+}
+
+// --- COMPONENT: Pairing for the Settings tab ---
+#[component]
+pub fn PairingPanel() -> Element {
+    let mut pin = use_signal(String::new);
+    let mut status = use_signal(|| None::<String>);
+
+    rsx! {
+        div { class: "glass-border-gradient !rounded-3xl w-full",
+            div { class: "glass-panel p-8 rounded-3xl flex flex-col gap-4",
+                div {
+                    h3 { class: "text-xl font-bold text-white", "Pairing" }
+                    p { class: "text-sm text-white/40",
+                        if TOKEN().is_some() {
+                            "This device may drive {ROBOT.read().name}"
+                        } else {
+                            "Enter the PIN shown by the robot to drive it from this device"
+                        }
+                    }
+                }
+                if TOKEN().is_some() {
+                    button {
+                        class: "self-start px-4 py-2 rounded-full text-sm font-bold text-red-400 hover:bg-red-500/20 transition-all",
+                        onclick: move |_| unpair(),
+                        "Unpair"
+                    }
+                } else {
+                    div { class: "flex gap-2",
+                        input {
+                            class: "flex-1 bg-transparent border-b border-white/20 focus:border-pink-500 outline-none py-1 font-mono tracking-[0.5em] select-text",
+                            inputmode: "numeric",
+                            autocomplete: "one-time-code",
+                            placeholder: "PIN",
+                            value: "{pin}",
+                            oninput: move |evt| pin.set(evt.value()),
+                        }
+                        button {
+                            class: "px-4 py-2 rounded-full text-sm font-bold glass-panel hover:bg-white/20 transition-all",
+                            disabled: pin.read().trim().is_empty(),
+                            onclick: move |_| async move {
+                                let entered = pin().trim().to_owned();
+                                status.set(Some("Pairing…".to_owned()));
+                                match pair(&entered).await {
+                                    Ok(()) => {
+                                        pin.set(String::new());
+                                        status.set(None);
+                                    }
+                                    Err(err) => status.set(Some(format!("Pairing failed: {err}"))),
+                                }
+                            },
+                            "Pair"
+                        }
+                    }
+                }
+                if let Some(status) = status() {
+                    p { class: "text-sm text-white/60", "{status}" }
+                }
+            }
+        }
+    }
+}
+
+// --- COMPONENT: Command signing for the Settings tab ---
+#[component]
+pub fn SigningPanel() -> Element {
+    let mut key = use_signal(String::new);
+
+    rsx! {
+        div { class: "glass-border-gradient !rounded-3xl w-full",
+            div { class: "glass-panel p-8 rounded-3xl flex flex-col gap-4",
+                div {
+                    h3 { class: "text-xl font-bold text-white", "Command Signing" }
+                    p { class: "text-sm text-white/40",
+                        if SIGNING_KEY().is_some() {
+                            "Commands are signed, so they can't be replayed or tampered with"
+                        } else {
+                            "Sign commands with a key shared with the robot, for shared networks"
+                        }
+                    }
+                }
+                if SIGNING_KEY().is_some() {
+                    button {
+                        class: "self-start px-4 py-2 rounded-full text-sm font-bold text-red-400 hover:bg-red-500/20 transition-all",
+                        onclick: move |_| {
+                            storage::remove(&signing_storage_key());
+                            *SIGNING_KEY.write() = None;
+                        },
+                        "Stop signing"
+                    }
+                } else {
+                    div { class: "flex gap-2",
+                        input {
+                            class: "flex-1 bg-transparent border-b border-white/20 focus:border-pink-500 outline-none py-1 font-mono select-text",
+                            r#type: "password",
+                            autocomplete: "off",
+                            placeholder: "Pre-shared key",
+                            value: "{key}",
+                            oninput: move |evt| key.set(evt.value()),
+                        }
+                        button {
+                            class: "px-4 py-2 rounded-full text-sm font-bold glass-panel hover:bg-white/20 transition-all",
+                            disabled: key.read().is_empty(),
+                            onclick: move |_| {
+                                let entered = key.replace(String::new());
+                                storage::save(&signing_storage_key(), &entered);
+                                *SIGNING_KEY.write() = Some(entered);
+                            },
+                            "Sign"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
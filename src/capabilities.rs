@@ -0,0 +1,397 @@
+//! What the selected robot says it accepts, from `GET /capabilities`.
+//!
+//! This is the handshake at startup: the UI sends its [`PROTOCOL_VERSION`] and the robot
+//! answers with its own, the commands it understands and its settings' limits. Controls for
+//! commands it doesn't list are disabled. Robots that don't answer are assumed to speak this
+//! protocol, take every command and use the bundled [`Setting`] ranges.
+
+use std::ops::RangeInclusive;
+
+use dioxus::prelude::*;
+use reqwasm::http::Request;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ROBOT, auth,
+    command::{Command, SafeRange, Setting, Status},
+    units::{self, Unit},
+};
+
+/// The protocol this UI speaks.
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Firmware protocols this UI can still drive.
+const COMPATIBLE: RangeInclusive<u32> = 1..=PROTOCOL_VERSION;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Capabilities {
+    #[serde(default = "first_protocol")]
+    pub protocol: u32,
+    #[serde(default)]
+    pub firmware: Option<String>,
+    /// Every action the firmware understands, or `None` if it didn't say.
+    #[serde(default)]
+    pub commands: Option<Vec<String>>,
+    #[serde(default)]
+    pub settings: Vec<SettingLimits>,
+}
+
+const fn first_protocol() -> u32 {
+    1
+}
+
+/// The values a setting accepts: anything from `min` to `max`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct SettingLimits {
+    pub action: String,
+    pub min: i32,
+    pub max: i32,
+    /// How far the slider moves per notch; typed and nudged values may fall in between.
+    #[serde(default = "default_step")]
+    pub step: i32,
+    #[serde(default)]
+    pub unit: Unit,
+    /// The unit the firmware takes, if not `unit`.
+    #[serde(default)]
+    pub wire: Option<Unit>,
+    pub default: i32,
+    /// Spread the slider logarithmically, for ranges spanning orders of magnitude.
+    #[serde(default)]
+    pub log_scale: bool,
+    #[serde(default)]
+    pub safe: Option<SafeRange>,
+}
+
+/// Notches on a logarithmic slider.
+pub const LOG_POSITIONS: i32 = 1000;
+
+const fn default_step() -> i32 {
+    1
+}
+
+impl SettingLimits {
+    /// Why `value` can't be sent, if it can't.
+    pub fn validate(&self, value: i32) -> Result<(), String> {
+        let Self { min, max, unit, .. } = *self;
+        if !(min..=max).contains(&value) {
+            return Err(format!("{value}{unit} is outside {min}{unit}–{max}{unit}"));
+        }
+        Ok(())
+    }
+
+    /// Whether `value` is in the safe range, if the setting has one.
+    pub fn is_safe(&self, value: i32) -> bool {
+        self.safe.is_none_or(|x| (x.min..=x.max).contains(&value))
+    }
+
+    /// Whether a command with `status` sets a value in the safe range. Statuses that aren't
+    /// values, or that can't be converted back from the firmware's unit, are left to the firmware.
+    pub fn is_safe_status(&self, status: Status) -> bool {
+        let sent = match status {
+            Status::Value(x) => f64::from(x),
+            Status::Decimal(x) => x.get(),
+            _ => return true,
+        };
+        let Some(value) = self.wire_unit().convert(sent, self.unit) else {
+            return true;
+        };
+        self.safe
+            .is_none_or(|x| (f64::from(x.min)..=f64::from(x.max)).contains(&value))
+    }
+
+    /// The safe value closest to `value`.
+    pub fn clamp_safe(&self, value: i32) -> i32 {
+        match self.safe {
+            Some(x) => value.clamp(x.min.max(self.min), x.max.min(self.max)),
+            None => value,
+        }
+    }
+
+    /// The unit values are sent in.
+    pub fn wire_unit(&self) -> Unit {
+        self.wire.unwrap_or(self.unit)
+    }
+
+    /// The command setting this to `value`, converted to the firmware's unit.
+    pub fn command(&self, value: i32) -> Command {
+        let status = units::to_wire(value, self.unit, self.wire_unit());
+        Command::new(self.action.clone(), status)
+    }
+
+    fn logarithmic(&self) -> bool {
+        self.log_scale && self.min > 0
+    }
+
+    /// The range input's bounds and step: the values themselves, or notches when logarithmic.
+    pub fn slider_range(&self) -> (i32, i32, i32) {
+        if self.logarithmic() {
+            (0, LOG_POSITIONS, 1)
+        } else {
+            (self.min, self.max, self.step)
+        }
+    }
+
+    /// Where the slider sits for `value`.
+    pub fn position(&self, value: i32) -> i32 {
+        if !self.logarithmic() {
+            return value;
+        }
+        let (min, max) = (f64::from(self.min), f64::from(self.max));
+        let fraction = (f64::from(value.max(self.min)) / min).ln() / (max / min).ln();
+        (fraction * f64::from(LOG_POSITIONS)).round() as i32
+    }
+
+    /// The value for a slider at `position`. Logarithmic sliders land on two significant
+    /// digits, so they stop at round numbers like 8000 instead of 8123.
+    pub fn value_at(&self, position: i32) -> i32 {
+        if !self.logarithmic() {
+            return position;
+        }
+        let (min, max) = (f64::from(self.min), f64::from(self.max));
+        let exact = min * (max / min).powf(f64::from(position) / f64::from(LOG_POSITIONS));
+        let magnitude = 10f64.powi(exact.log10().floor() as i32 - 1).max(1.0);
+        let value = ((exact / magnitude).round() * magnitude) as i32;
+        value.clamp(self.min, self.max)
+    }
+
+    /// Limits that can't describe any slider, e.g. `min` above `max`.
+    pub fn problem(&self) -> Option<&'static str> {
+        if self.min > self.max {
+            Some("min is above max")
+        } else if self.step <= 0 {
+            Some("step isn't positive")
+        } else if self.unit.quantity() != self.wire_unit().quantity() {
+            Some("unit doesn't measure what the firmware takes")
+        } else if self.validate(self.default).is_err() {
+            Some("default isn't an accepted value")
+        } else if !self.is_safe(self.default) {
+            Some("default isn't safe")
+        } else {
+            None
+        }
+    }
+}
+
+impl From<Setting> for SettingLimits {
+    fn from(x: Setting) -> Self {
+        Self {
+            action: x.action.to_owned(),
+            min: x.min,
+            max: x.max,
+            step: x.step,
+            unit: x.unit,
+            wire: Some(x.wire),
+            default: x.default,
+            log_scale: x.log_scale,
+            safe: x.safe,
+        }
+    }
+}
+
+/// `None` until the selected robot answers, and for robots that never do.
+static CAPABILITIES: GlobalSignal<Option<Capabilities>> = Signal::global(|| None);
+/// The robot last asked for its capabilities, once it answered or failed to.
+static ASKED: GlobalSignal<Option<String>> = Signal::global(|| None);
+
+/// Whether the selected robot was asked for its capabilities, so [`limits`] are its own.
+pub fn loaded() -> bool {
+    ASKED.read().as_ref() == Some(&ROBOT.read().name)
+}
+
+/// The limits for `bundled`'s action, as reported by the robot if it did.
+pub fn limits(bundled: Setting) -> SettingLimits {
+    CAPABILITIES
+        .read()
+        .as_ref()
+        .and_then(|x| x.settings.iter().find(|x| x.action == bundled.action))
+        .cloned()
+        .unwrap_or_else(|| bundled.into())
+}
+
+/// Whether the robot takes `action`.
+pub fn supports(action: &str) -> bool {
+    CAPABILITIES
+        .read()
+        .as_ref()
+        .and_then(|x| x.commands.as_ref())
+        .is_none_or(|x| x.iter().any(|x| x == action))
+}
+
+/// The firmware version the robot reported, if it did.
+pub fn firmware() -> Option<String> {
+    CAPABILITIES.read().as_ref()?.firmware.clone()
+}
+
+/// Why the robot's firmware can't be driven by this UI, if it can't.
+fn incompatibility(protocol: u32) -> Option<String> {
+    if protocol < *COMPATIBLE.start() {
+        Some(format!(
+            "The robot's firmware is too old (protocol {protocol}, this app needs {}). Update the robot.",
+            COMPATIBLE.start()
+        ))
+    } else if protocol > *COMPATIBLE.end() {
+        Some(format!(
+            "The robot's firmware is too new (protocol {protocol}, this app speaks up to {}). Update this app.",
+            COMPATIBLE.end()
+        ))
+    } else {
+        None
+    }
+}
+
+async fn fetch_capabilities(base_url: &str) -> Option<Capabilities> {
+    let resp = auth::authorize(Request::get(&format!("{base_url}/capabilities")))
+        .header("X-Protocol-Version", &PROTOCOL_VERSION.to_string())
+        .send()
+        .await
+        .ok()?;
+    if resp.status() != 200 {
+        return None;
+    }
+    let mut capabilities = resp
+        .json::<Capabilities>()
+        .await
+        .inspect_err(|err| warn!("Ignoring the robot's capabilities: {err}"))
+        .ok()?;
+    capabilities.settings.retain(|x| match x.problem() {
+        Some(problem) => {
+            warn!("Ignoring the robot's limits for `{}`: {problem}", x.action);
+            false
+        }
+        None => true,
+    });
+    Some(capabilities)
+}
+
+/// Asks the selected robot for its capabilities whenever the robot changes, once paired.
+pub fn use_capabilities() {
+    use_effect(|| {
+        let robot = ROBOT.read().clone();
+        *CAPABILITIES.write() = None;
+        if !auth::loaded() {
+            return;
+        }
+        spawn(async move {
+            *CAPABILITIES.write() = fetch_capabilities(&robot.base_url).await;
+            *ASKED.write() = Some(robot.name);
+        });
+    });
+}
+
+// --- COMPONENT: Firmware compatibility warning ---
+#[component]
+pub fn CompatibilityBanner() -> Element {
+    let Some(message) = CAPABILITIES
+        .read()
+        .as_ref()
+        .and_then(|x| incompatibility(x.protocol))
+    else {
+        return VNode::empty();
+    };
+
+    rsx! {
+        div { class: "absolute top-24 left-1/2 -translate-x-1/2 z-50 max-w-xl px-6 py-3 rounded-2xl glass-panel border border-red-500/40 text-sm font-bold text-red-300 text-center",
+            "{message}"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{BLINK_RATE, PWM_FREQUENCY, PWM_PERCENTAGE, SETTINGS, Status};
+
+    #[test]
+    fn validates_range_and_step() {
+        let limits = SettingLimits::from(PWM_PERCENTAGE);
+        assert_eq!(limits.validate(20), Ok(()));
+        assert_eq!(limits.validate(100), Ok(()));
+        assert_eq!(limits.validate(22), Ok(()));
+        assert!(limits.validate(0).is_err());
+        assert!(limits.validate(101).is_err());
+    }
+
+    #[test]
+    fn sends_values_in_the_unit_the_firmware_takes() {
+        let frequency = SettingLimits::from(PWM_FREQUENCY);
+        assert_eq!(
+            frequency.command(8000).to_string(),
+            "cmd=frequency_kilohertz&status=8000"
+        );
+        let reported = SettingLimits {
+            wire: Some(Unit::Kilohertz),
+            ..frequency
+        };
+        assert_eq!(
+            reported.command(500).to_string(),
+            "cmd=frequency_kilohertz&status=0.5"
+        );
+        // Layout sliders for actions that aren't settings carry no unit
+        let slider = SettingLimits {
+            action: "arm_speed".to_owned(),
+            min: 0,
+            max: 100,
+            step: 1,
+            unit: Unit::Plain,
+            wire: None,
+            default: 50,
+            log_scale: false,
+            safe: None,
+        };
+        assert_eq!(slider.command(42).status, Status::Value(42));
+    }
+
+    #[test]
+    fn judges_sent_values_in_the_settings_unit() {
+        let frequency = SettingLimits {
+            wire: Some(Unit::Kilohertz),
+            ..SettingLimits::from(PWM_FREQUENCY)
+        };
+        assert!(frequency.is_safe_status(frequency.command(8000).status));
+        assert!(!frequency.is_safe_status(frequency.command(500).status));
+        let pwm = SettingLimits::from(PWM_PERCENTAGE);
+        assert!(!pwm.is_safe_status(Status::Value(100)));
+        assert!(pwm.is_safe_status(Status::Pressed));
+    }
+
+    #[test]
+    fn holds_back_to_the_safe_range() {
+        let limits = SettingLimits::from(PWM_PERCENTAGE);
+        assert!(limits.is_safe(90));
+        assert!(!limits.is_safe(100));
+        assert_eq!(limits.clamp_safe(100), 90);
+        assert_eq!(limits.clamp_safe(50), 50);
+        assert!(SettingLimits::from(BLINK_RATE).is_safe(150));
+    }
+
+    #[test]
+    fn log_slider_spans_the_range_on_round_values() {
+        let limits = SettingLimits::from(PWM_FREQUENCY);
+        assert_eq!(limits.value_at(0), 500);
+        assert_eq!(limits.value_at(LOG_POSITIONS), 48000);
+        assert_eq!(limits.value_at(limits.position(8000)), 8000);
+        assert!(limits.value_at(LOG_POSITIONS / 2) % 100 == 0);
+    }
+
+    #[test]
+    fn flags_firmware_outside_the_compatible_protocols() {
+        assert_eq!(incompatibility(PROTOCOL_VERSION), None);
+        assert!(incompatibility(0).unwrap().contains("too old"));
+        assert!(
+            incompatibility(PROTOCOL_VERSION + 1)
+                .unwrap()
+                .contains("too new")
+        );
+    }
+
+    #[test]
+    fn bundled_settings_are_consistent() {
+        for setting in SETTINGS {
+            assert_eq!(
+                SettingLimits::from(setting).problem(),
+                None,
+                "{}",
+                setting.action
+            );
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! The commands the UI sends to the robot, and the one place they're sent from.
+
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+
+use dioxus::prelude::*;
+use reqwasm::http::Request;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ROBOT, auth, capabilities,
+    command_log::{self, Entry},
+    governor,
+    haptics::{self, Pulse},
+    lease, recorder, settings,
+    time::Instant,
+    units::{Decimal, Unit},
+};
+
+/// Actions the robot keeps doing between their `pressed` and `released` commands.
+pub const MOMENTARY_ACTIONS: [&str; 8] = [
+    "go_front",
+    "go_back",
+    "turn_left",
+    "turn_right",
+    "pull_up",
+    "pull_down",
+    "arm_up",
+    "arm_down",
+];
+
+/// An action that takes a value, and the values the firmware accepts for it unless the robot
+/// reports its own.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Setting {
+    pub action: &'static str,
+    pub min: i32,
+    pub max: i32,
+    pub step: i32,
+    /// The unit `min`, `max` and values are in.
+    pub unit: Unit,
+    /// The unit the firmware takes, which values are converted to when sent.
+    pub wire: Unit,
+    pub default: i32,
+    pub log_scale: bool,
+    /// Values the hardware takes without risk; others are only sent once confirmed.
+    pub safe: Option<SafeRange>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct SafeRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+pub const BLINK_RATE: Setting = Setting {
+    action: "blink_rate",
+    min: 50,
+    max: 150,
+    step: 5,
+    unit: Unit::Milliseconds,
+    wire: Unit::Milliseconds,
+    default: 75,
+    log_scale: false,
+    safe: None,
+};
+
+pub const PWM_PERCENTAGE: Setting = Setting {
+    action: "pwm_percentage",
+    min: 20,
+    max: 100,
+    step: 5,
+    unit: Unit::Percent,
+    wire: Unit::Percent,
+    default: 90,
+    log_scale: false,
+    // Full duty cycle overheats the motor drivers when stalled
+    safe: Some(SafeRange { min: 20, max: 90 }),
+};
+
+pub const PWM_FREQUENCY: Setting = Setting {
+    action: "frequency_kilohertz",
+    min: 500,
+    max: 48000,
+    step: 500,
+    unit: Unit::Hertz,
+    // Despite the name, the firmware takes Hz
+    wire: Unit::Hertz,
+    default: 8000,
+    log_scale: true,
+    // Low frequencies make the motors vibrate and whine under load
+    safe: Some(SafeRange {
+        min: 2000,
+        max: 48000,
+    }),
+};
+
+pub const SETTINGS: [Setting; 3] = [BLINK_RATE, PWM_PERCENTAGE, PWM_FREQUENCY];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Status {
+    Pressed,
+    Released,
+    BlinkOnce,
+    On,
+    Off,
+    Value(i32),
+    /// A value that isn't whole in the firmware's unit, e.g. 0.5 kHz.
+    Decimal(Decimal),
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pressed => write!(f, "pressed"),
+            Self::Released => write!(f, "released"),
+            Self::BlinkOnce => write!(f, "blink_once"),
+            Self::On => write!(f, "on"),
+            Self::Off => write!(f, "off"),
+            Self::Value(x) => write!(f, "{x}"),
+            Self::Decimal(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "pressed" => Self::Pressed,
+            "released" => Self::Released,
+            "blink_once" => Self::BlinkOnce,
+            "on" => Self::On,
+            "off" => Self::Off,
+            x => match x.parse() {
+                Ok(x) => Self::Value(x),
+                Err(_) => Self::Decimal(x.parse().map_err(|_| format!("unknown status `{x}`"))?),
+            },
+        })
+    }
+}
+
+impl From<Status> for String {
+    fn from(status: Status) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for Status {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Command {
+    pub action: Cow<'static, str>,
+    pub status: Status,
+}
+
+impl Command {
+    pub fn new(action: impl Into<Cow<'static, str>>, status: Status) -> Self {
+        Self {
+            action: action.into(),
+            status,
+        }
+    }
+
+    pub fn pressed(action: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(action, Status::Pressed)
+    }
+
+    pub fn released(action: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(action, Status::Released)
+    }
+
+    /// The command undoing this one, if it's a press, a held lever's deflection or switches
+    /// something on.
+    pub fn release(&self) -> Option<Self> {
+        match self.status {
+            Status::Pressed => Some(Self::released(self.action.clone())),
+            Status::On => Some(Self::new(self.action.clone(), Status::Off)),
+            Status::Value(x) if x != 0 && MOMENTARY_ACTIONS.contains(&&*self.action) => {
+                Some(Self::new(self.action.clone(), Status::Value(0)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cmd={}&status={}", self.action, self.status)
+    }
+}
+
+/// `command` as it may be sent, or why this device won't send it at all.
+fn check(command: &Command) -> Result<Command, String> {
+    if !auth::loaded() {
+        return Err("this device's pairing hasn't loaded yet".to_owned());
+    }
+    if !lease::may_drive() {
+        return Err("another device is driving".to_owned());
+    }
+    if !capabilities::supports(&command.action) {
+        return Err(format!("the robot doesn't support `{}`", command.action));
+    }
+    // Macros record what was meant, so they're limited afresh when played back
+    recorder::capture(command);
+    settings::admit(command)?;
+    governor::govern(command.clone())
+}
+
+/// Sends `command`, telling why if the robot didn't take it.
+pub async fn try_send(command: Command) -> Result<(), String> {
+    let timestamp_ms = command_log::unix_millis();
+    let command = match check(&command) {
+        Ok(x) => x,
+        Err(error) => {
+            // A post-mortem needs what this device held back as much as what the robot refused
+            command_log::push(Entry {
+                timestamp_ms,
+                command,
+                http_status: None,
+                latency_ms: 0,
+                error: Some(error.clone()),
+            });
+            return Err(error);
+        }
+    };
+
+    let params = command.to_string();
+    let url = format!("{}/controller", ROBOT.read().base_url);
+    let sent = Instant::now();
+    let request = auth::authorize(lease::identify(Request::post(&url)));
+    let resp = auth::sign(request, &params)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(&params)
+        .send()
+        .await;
+    let latency_ms = sent.elapsed().as_millis() as u64;
+
+    let (http_status, error) = match resp {
+        Ok(x) if x.status() != 200 => {
+            error!("Failed: {} code: {}", params, x.status());
+            haptics::pulse(Pulse::Alert);
+            let reason = match auth::check(&x).await {
+                Some(refused) => refused.to_string(),
+                // Robots explain rejected values in the body, e.g. "pwm_percentage must be 20-100"
+                None => x
+                    .text()
+                    .await
+                    .ok()
+                    .filter(|x| !x.trim().is_empty())
+                    .unwrap_or_else(|| format!("the robot answered {}", x.status())),
+            };
+            (Some(x.status()), Some(reason))
+        }
+        Err(err) => {
+            error!("Error: {} - {}", params, err);
+            haptics::pulse(Pulse::Alert);
+            (None, Some(err.to_string()))
+        }
+        Ok(x) => (Some(x.status()), None),
+    };
+
+    command_log::push(Entry {
+        timestamp_ms,
+        command,
+        http_status,
+        latency_ms,
+        error: error.clone(),
+    });
+    error.map_or(Ok(()), Err)
+}
+
+pub async fn send_command(command: Command) {
+    // Failures are logged and felt; callers that care use `try_send`
+    let _ = try_send(command).await;
+}
@@ -0,0 +1,249 @@
+//! Every command the UI sent this session, with how the robot answered, for post-mortems.
+
+use std::collections::VecDeque;
+
+use dioxus::prelude::*;
+use serde::Serialize;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::command::Command;
+
+/// Roughly ten minutes of continuous driving; older entries are dropped first.
+const CAPACITY: usize = 2000;
+
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct Entry {
+    /// Milliseconds since the Unix epoch, when the request was sent.
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub command: Command,
+    /// `None` when the request never got an answer, or was never sent.
+    pub http_status: Option<u16>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// `field` as a quoted CSV field, since action names and errors can hold commas and quotes.
+fn quoted(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+impl Entry {
+    fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.timestamp_ms,
+            quoted(&self.command.action),
+            quoted(&self.command.status.to_string()),
+            self.http_status.map(|x| x.to_string()).unwrap_or_default(),
+            self.latency_ms,
+            quoted(self.error.as_deref().unwrap_or_default()),
+        )
+    }
+}
+
+static LOG: GlobalSignal<VecDeque<Entry>> = Signal::global(VecDeque::new);
+
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_millis() as u64)
+}
+
+pub fn push(entry: Entry) {
+    let mut log = LOG.write();
+    if log.len() == CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(entry);
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    JsonLines,
+    Csv,
+}
+
+/// The file name, MIME type and contents of `entries` exported as `format`.
+fn render(entries: &[Entry], format: Format) -> (&'static str, &'static str, String) {
+    match format {
+        Format::JsonLines => (
+            "commands.jsonl",
+            "application/jsonl",
+            entries
+                .iter()
+                .filter_map(|x| serde_json::to_string(x).ok())
+                .map(|x| x + "\n")
+                .collect::<String>(),
+        ),
+        Format::Csv => (
+            "commands.csv",
+            "text/csv",
+            entries.iter().fold(
+                "timestamp_ms,action,status,http_status,latency_ms,error\n".to_owned(),
+                |csv, x| csv + &x.to_csv_row() + "\n",
+            ),
+        ),
+    }
+}
+
+fn export(entries: &[Entry], format: Format) {
+    let (filename, mime, content) = render(entries, format);
+
+    let eval = document::eval(
+        r"
+            const [filename, mime, content] = await dioxus.recv();
+            const link = document.createElement('a');
+            link.href = URL.createObjectURL(new Blob([content], { type: mime }));
+            link.download = filename;
+            link.click();
+            // Some browsers only start the download once the click's task is over
+            setTimeout(() => URL.revokeObjectURL(link.href));
+        ",
+    );
+    if let Err(err) = eval.send((filename, mime, content)) {
+        error!("Failed to export the command log: {err}");
+    }
+}
+
+// --- COMPONENT: Log tab ---
+#[component]
+pub fn CommandLog() -> Element {
+    let mut filter = use_signal(|| None::<String>);
+
+    let actions = use_memo(|| {
+        let mut actions = LOG
+            .read()
+            .iter()
+            .map(|x| x.command.action.to_string())
+            .collect::<Vec<_>>();
+        actions.sort();
+        actions.dedup();
+        actions
+    });
+    let shown = use_memo(move || {
+        LOG.read()
+            .iter()
+            .filter(|x| {
+                filter
+                    .read()
+                    .as_deref()
+                    .is_none_or(|f| x.command.action == f)
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    });
+    // The earliest entry, as the clock may have been set back since
+    let session_start = use_memo(|| LOG.read().iter().map(|x| x.timestamp_ms).min().unwrap_or(0));
+
+    rsx! {
+        div { class: "w-full max-w-3xl flex justify-between items-center gap-4",
+            select {
+                class: "px-4 py-2 rounded-full text-sm font-bold glass-panel bg-transparent outline-none",
+                onchange: move |evt| {
+                    let value = evt.value();
+                    filter.set((!value.is_empty()).then_some(value));
+                },
+                option { value: "", "All controls" }
+                for action in actions() {
+                    option { value: "{action}", selected: filter.read().as_ref() == Some(&action), "{action}" }
+                }
+            }
+            div { class: "flex gap-2",
+                button {
+                    class: "px-4 py-2 rounded-full text-sm font-bold glass-panel hover:bg-white/20 transition-all",
+                    onclick: move |_| export(&shown.read(), Format::JsonLines),
+                    "Export JSONL"
+                }
+                button {
+                    class: "px-4 py-2 rounded-full text-sm font-bold glass-panel hover:bg-white/20 transition-all",
+                    onclick: move |_| export(&shown.read(), Format::Csv),
+                    "Export CSV"
+                }
+            }
+        }
+
+        div { class: "glass-border-gradient !rounded-3xl w-full max-w-3xl",
+            div { class: "glass-panel p-6 rounded-3xl flex flex-col gap-1 font-mono text-xs",
+                if shown.read().is_empty() {
+                    p { class: "text-sm text-white/40 text-center", "No commands sent yet" }
+                }
+                // Newest first, since that's what's being looked for right after something goes wrong
+                for entry in shown.read().iter().rev() {
+                    div { class: "grid grid-cols-[5rem_1fr_6rem_3rem_4rem] gap-2",
+                        span { class: "text-white/40", "+{entry.timestamp_ms.saturating_sub(session_start()) as f64 / 1000.0:.3}s" }
+                        span { class: "font-bold", "{entry.command.action}" }
+                        span { class: "text-white/60", "{entry.command.status}" }
+                        match (entry.http_status, &entry.error) {
+                            (Some(200), _) => rsx! { span { class: "text-green-400", "200" } },
+                            (Some(status), _) => rsx! { span { class: "text-red-400", "{status}" } },
+                            (None, error) => rsx! { span { class: "text-red-400", title: error.clone(), "ERR" } },
+                        }
+                        span { class: "text-white/40 text-right", "{entry.latency_ms}ms" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::Status;
+
+    fn entries() -> Vec<Entry> {
+        vec![
+            Entry {
+                timestamp_ms: 1_000,
+                command: Command::pressed("go_front"),
+                http_status: Some(200),
+                latency_ms: 12,
+                error: None,
+            },
+            Entry {
+                timestamp_ms: 1_250,
+                command: Command::new("blink_rate", Status::Value(100)),
+                http_status: None,
+                latency_ms: 3_000,
+                error: Some(r#"timed out "again""#.to_owned()),
+            },
+            // Layouts may name actions anything
+            Entry {
+                timestamp_ms: 1_500,
+                command: Command::pressed(r#"lift, "fast""#),
+                http_status: Some(200),
+                latency_ms: 8,
+                error: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn exports_csv_with_quoted_errors() {
+        let (filename, _, csv) = render(&entries(), Format::Csv);
+        assert_eq!(filename, "commands.csv");
+        assert_eq!(
+            csv,
+            "timestamp_ms,action,status,http_status,latency_ms,error\n\
+             1000,\"go_front\",\"pressed\",200,12,\"\"\n\
+             1250,\"blink_rate\",\"100\",,3000,\"timed out \"\"again\"\"\"\n\
+             1500,\"lift, \"\"fast\"\"\",\"pressed\",200,8,\"\"\n"
+        );
+    }
+
+    #[test]
+    fn exports_one_json_object_per_line() {
+        let (filename, _, jsonl) = render(&entries(), Format::JsonLines);
+        assert_eq!(filename, "commands.jsonl");
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|x| serde_json::from_str(x).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["action"], "go_front");
+        assert_eq!(lines[0]["http_status"], 200);
+        assert_eq!(lines[1]["http_status"], serde_json::Value::Null);
+        assert_eq!(lines[1]["error"], r#"timed out "again""#);
+    }
+}
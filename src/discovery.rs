@@ -0,0 +1,216 @@
+//! Finding robots on the local network, for builds that aren't served by the robot itself.
+//!
+//! Robots advertise [`SERVICE_TYPE`] over mDNS with `name` and `fw` TXT records. Firmware without
+//! an mDNS responder can instead answer a UDP broadcast of [`PROBE`] on [`DISCOVERY_PORT`] with a
+//! `name=<name>;fw=<version>;port=<http port>` datagram.
+
+use std::{
+    io::ErrorKind,
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    thread,
+    time::{Duration, Instant},
+};
+
+use dioxus::prelude::*;
+use futures_util::StreamExt;
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+
+use crate::{ROBOT, Robot};
+
+const SERVICE_TYPE: &str = "_robot-ctl._tcp.local.";
+const DISCOVERY_PORT: u16 = 4210;
+const PROBE: &[u8] = b"controller-ui/discover";
+const SCAN_DURATION: Duration = Duration::from_secs(3);
+
+/// Runs both discovery methods on background threads, streaming every answer into `tx`.
+///
+/// The channel closes once both methods have given up, after roughly [`SCAN_DURATION`].
+fn scan(tx: UnboundedSender<Robot>) {
+    let mdns_tx = tx.clone();
+    thread::spawn(move || {
+        if let Err(err) = browse_mdns(&mdns_tx) {
+            warn!("mDNS discovery failed: {err}");
+        }
+    });
+    thread::spawn(move || {
+        if let Err(err) = probe_broadcast(&tx) {
+            warn!("Broadcast discovery failed: {err}");
+        }
+    });
+}
+
+fn browse_mdns(tx: &UnboundedSender<Robot>) -> mdns_sd::Result<()> {
+    let daemon = ServiceDaemon::new()?;
+    let events = daemon.browse(SERVICE_TYPE)?;
+    let deadline = Instant::now() + SCAN_DURATION;
+
+    while let Ok(event) = events.recv_deadline(deadline) {
+        let ServiceEvent::ServiceResolved(service) = event else {
+            continue;
+        };
+        let Some(addr) = service.get_addresses_v4().into_iter().next() else {
+            continue;
+        };
+        let robot = Robot {
+            name: service
+                .get_property_val_str("name")
+                .unwrap_or_else(|| service.get_hostname().trim_end_matches(".local."))
+                .to_owned(),
+            firmware: service
+                .get_property_val_str("fw")
+                .unwrap_or("unknown")
+                .to_owned(),
+            base_url: format!("http://{addr}:{}", service.get_port()),
+        };
+        if tx.unbounded_send(robot).is_err() {
+            break;
+        }
+    }
+
+    // The daemon only stops browsing once it's shut down; there's nothing useful to do if it's already gone.
+    let _ = daemon.shutdown();
+    Ok(())
+}
+
+fn probe_broadcast(tx: &UnboundedSender<Robot>) -> std::io::Result<()> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+    socket.send_to(PROBE, (Ipv4Addr::BROADCAST, DISCOVERY_PORT))?;
+
+    let deadline = Instant::now() + SCAN_DURATION;
+    let mut buf = [0; 256];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(());
+        }
+        socket.set_read_timeout(Some(remaining))?;
+
+        let (len, from) = match socket.recv_from(&mut buf) {
+            Ok(x) => x,
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+        if let Some(robot) = parse_announcement(&buf[..len], from)
+            && tx.unbounded_send(robot).is_err()
+        {
+            return Ok(());
+        }
+    }
+}
+
+/// Parses a broadcast reply; replies without a `name` aren't from a robot.
+fn parse_announcement(datagram: &[u8], from: SocketAddr) -> Option<Robot> {
+    let text = std::str::from_utf8(datagram).ok()?;
+    let (mut name, mut firmware, mut port) = (None, "unknown", 80u16);
+    // Some firmware ends the list with a `;`
+    for field in text.trim().split(';').filter(|x| !x.is_empty()) {
+        match field.split_once('=')? {
+            ("name", val) => name = Some(val),
+            ("fw", val) => firmware = val,
+            ("port", val) => port = val.parse().ok()?,
+            _ => {}
+        }
+    }
+
+    Some(Robot {
+        name: name?.to_owned(),
+        firmware: firmware.to_owned(),
+        base_url: format!("http://{}:{port}", from.ip()),
+    })
+}
+
+#[component]
+pub fn RobotPicker() -> Element {
+    let mut found = use_signal(Vec::<Robot>::new);
+    let mut scanning = use_signal(|| false);
+
+    let mut start_scan = move || {
+        found.clear();
+        scanning.set(true);
+
+        let (tx, mut rx) = futures_channel::mpsc::unbounded();
+        scan(tx);
+        spawn(async move {
+            while let Some(robot) = rx.next().await {
+                // Robots answering both mDNS and the broadcast probe would otherwise show up twice.
+                if !found.iter().any(|x| x.base_url == robot.base_url) {
+                    found.push(robot);
+                }
+            }
+            scanning.set(false);
+        });
+    };
+    use_hook(start_scan);
+
+    rsx! {
+        div { class: "glass-border-gradient !rounded-3xl w-full",
+            div { class: "glass-panel p-8 rounded-3xl flex flex-col gap-6",
+                div { class: "flex justify-between items-end",
+                    div {
+                        h3 { class: "text-xl font-bold text-white", "Robots" }
+                        p { class: "text-sm text-white/40", "Pick a robot advertising itself on this network" }
+                    }
+                    button {
+                        class: "px-6 py-2 rounded-full text-sm font-bold glass-panel hover:bg-white/20 transition-all disabled:opacity-40",
+                        disabled: scanning(),
+                        onclick: move |_| start_scan(),
+                        if scanning() { "Scanning…" } else { "Rescan" }
+                    }
+                }
+
+                if found.is_empty() && !scanning() {
+                    p { class: "text-sm text-white/40 text-center", "No robots found" }
+                }
+
+                for robot in found() {
+                    button {
+                        key: "{robot.base_url}",
+                        class: format!("flex justify-between items-center px-6 py-3 rounded-2xl transition-all {}",
+                            if *ROBOT.read() == robot { "bg-white/20 shadow-inner" } else { "hover:bg-white/10" }),
+                        onclick: {
+                            let robot = robot.clone();
+                            move |_| *ROBOT.write() = robot.clone()
+                        },
+                        div { class: "flex flex-col items-start",
+                            span { class: "font-bold", "{robot.name}" }
+                            span { class: "text-xs text-white/40 font-mono", "{robot.base_url}" }
+                        }
+                        span { class: "text-sm text-white/60 font-mono", "{robot.firmware}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(datagram: &str) -> Option<Robot> {
+        parse_announcement(datagram.as_bytes(), "192.168.4.1:4210".parse().unwrap())
+    }
+
+    #[test]
+    fn parses_announcements() {
+        let robot = parse("name=Rover;fw=1.2;port=8080").unwrap();
+        assert_eq!(robot.name, "Rover");
+        assert_eq!(robot.firmware, "1.2");
+        assert_eq!(robot.base_url, "http://192.168.4.1:8080");
+        assert_eq!(parse("name=Rover;fw=1.2;\n").unwrap().firmware, "1.2");
+        assert_eq!(
+            parse("name=Rover").unwrap().base_url,
+            "http://192.168.4.1:80"
+        );
+    }
+
+    #[test]
+    fn ignores_other_datagrams() {
+        assert_eq!(parse("fw=1.2;port=80"), None);
+        assert_eq!(parse("name=Rover;port=http"), None);
+        assert_eq!(parse("hello"), None);
+    }
+}
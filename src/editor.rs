@@ -0,0 +1,455 @@
+//! Editing the current [`Layout`] on the device: drag controls around, resize them, add and
+//! remove them, and bind their commands.
+//!
+//! Edits happen on a draft, so nothing changes on the Control tab until it's saved.
+
+use dioxus::prelude::*;
+
+use crate::{
+    ButtonMode, Direction,
+    command::{MOMENTARY_ACTIONS, SETTINGS},
+    layout::{self, Control, ControlKind, Directions, LAYOUT, Layout, LayoutControl, Position},
+};
+
+const MIN_SCALE: f64 = 0.5;
+const MAX_SCALE: f64 = 2.0;
+/// Pixels of diagonal drag on the resize handle per 1.0 of scale.
+const RESIZE_PIXELS: f64 = 200.0;
+
+/// A change to the selected control.
+type Edit = Box<dyn FnOnce(&mut Control)>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Drag {
+    Move { start: (f64, f64), from: Position },
+    Resize { start: (f64, f64), from: f64 },
+}
+
+fn new_control(kind: ControlKind, title: &str) -> Control {
+    Control {
+        title: title.to_owned(),
+        kind,
+        position: Position { x: 0.5, y: 0.5 },
+        scale: 1.0,
+    }
+}
+
+/// Controls offered by the "add" buttons, with empty bindings to fill in.
+fn templates() -> [(&'static str, Control); 4] {
+    [
+        (
+            "+ Stick",
+            new_control(
+                ControlKind::Analog {
+                    commands: Directions::default(),
+                },
+                "Stick",
+            ),
+        ),
+        (
+            "+ Lever",
+            new_control(
+                ControlKind::Lever {
+                    commands: Directions::default(),
+                    angle: 0.0,
+                    hold: false,
+                },
+                "Lever",
+            ),
+        ),
+        (
+            "+ Slider",
+            new_control(
+                ControlKind::Slider {
+                    command: String::new(),
+                    min: 0,
+                    max: 100,
+                    step: 1,
+                    initial: 50,
+                },
+                "Slider",
+            ),
+        ),
+        (
+            "+ Button",
+            new_control(
+                ControlKind::Button {
+                    command: String::new(),
+                    mode: ButtonMode::Momentary,
+                },
+                "Button",
+            ),
+        ),
+    ]
+}
+
+// --- COMPONENT: Layout edit mode for the Control tab ---
+#[component]
+pub fn LayoutEditor(ondone: EventHandler) -> Element {
+    let mut draft = use_signal(|| LAYOUT.cloned());
+    let mut selected = use_signal(|| None::<usize>);
+    let mut drag = use_signal(|| None::<(usize, Drag)>);
+    let mut surface = use_signal(|| (1.0, 1.0));
+    let mut status = use_signal(|| None::<Result<&'static str, String>>);
+    // Layouts that can't work aren't kept, here or on the robot
+    let problem = draft
+        .read()
+        .controls
+        .iter()
+        .find_map(|x| Some(format!("{}: {}", x.title, x.problem()?)));
+
+    let save = move |_| {
+        layout::save_local(&draft.read());
+        *LAYOUT.write() = draft();
+        ondone.call(());
+    };
+    let upload = move |_| async move {
+        let result = layout::upload(&draft.read()).await;
+        if result.is_ok() {
+            *LAYOUT.write() = draft();
+        }
+        status.set(Some(result.map(|()| "Uploaded")));
+    };
+    let revert = move |_| async move {
+        layout::forget_local();
+        let layout = layout::load().await;
+        draft.set(layout.clone());
+        *LAYOUT.write() = layout;
+        selected.set(None);
+        status.set(Some(Ok("Reverted to the robot's layout")));
+    };
+
+    rsx! {
+        div { class: "absolute inset-0 flex flex-col gap-4",
+            // Toolbar
+            div { class: "flex flex-wrap items-center gap-2 z-20",
+                for (label, template) in templates() {
+                    button {
+                        class: "px-4 py-2 rounded-full text-sm font-bold glass-panel text-white/60 hover:bg-white/20 transition-all",
+                        onclick: move |_| {
+                            draft.write().controls.push(template.clone());
+                            selected.set(Some(draft.peek().controls.len() - 1));
+                        },
+                        "{label}"
+                    }
+                }
+
+                div { class: "flex-1" }
+
+                if let Some(problem) = &problem {
+                    span { class: "text-sm text-red-400", "{problem}" }
+                }
+                match status() {
+                    Some(Ok(msg)) => rsx! { span { class: "text-sm text-green-400", "{msg}" } },
+                    Some(Err(err)) => rsx! { span { class: "text-sm text-red-400", "Upload failed: {err}" } },
+                    None => rsx! {},
+                }
+                button {
+                    class: "px-4 py-2 rounded-full text-sm font-bold text-white/40 hover:text-white/80 transition-all",
+                    onclick: revert,
+                    "Revert"
+                }
+                button {
+                    class: "px-4 py-2 rounded-full text-sm font-bold text-white/40 hover:text-white/80 transition-all",
+                    onclick: move |_| ondone.call(()),
+                    "Cancel"
+                }
+                button {
+                    class: "px-4 py-2 rounded-full text-sm font-bold glass-panel hover:bg-white/20 transition-all disabled:opacity-30",
+                    disabled: problem.is_some(),
+                    onclick: upload,
+                    "Upload to robot"
+                }
+                button {
+                    class: "px-4 py-2 rounded-full text-sm font-bold bg-pink-500/40 hover:bg-pink-500/60 transition-all disabled:opacity-30",
+                    disabled: problem.is_some(),
+                    onclick: save,
+                    "Save"
+                }
+            }
+
+            // Canvas
+            div {
+                class: "relative flex-1 rounded-3xl border-2 border-dashed border-white/20",
+                onresize: move |evt| {
+                    if let Ok(size) = evt.get_border_box_size() {
+                        surface.set((size.width, size.height));
+                    }
+                },
+                onpointerdown: move |_| selected.set(None),
+                onpointermove: move |evt| {
+                    let Some((index, kind)) = drag() else {
+                        return;
+                    };
+                    let point = evt.client_coordinates();
+                    let mut draft = draft.write();
+                    let control = &mut draft.controls[index];
+                    match kind {
+                        Drag::Move { start, from } => {
+                            let (width, height) = surface();
+                            control.position = Position {
+                                x: (from.x + (point.x - start.0) / width).clamp(0.0, 1.0),
+                                y: (from.y + (point.y - start.1) / height).clamp(0.0, 1.0),
+                            };
+                        }
+                        Drag::Resize { start, from } => {
+                            let delta = (point.x - start.0 + point.y - start.1) / RESIZE_PIXELS;
+                            control.scale = (from + delta).clamp(MIN_SCALE, MAX_SCALE);
+                        }
+                    }
+                },
+                onpointerup: move |_| drag.set(None),
+                onpointerleave: move |_| drag.set(None),
+
+                for (i, control) in draft().controls.into_iter().enumerate() {
+                    div {
+                        key: "{control.key(i)}",
+                        class: "absolute -translate-x-1/2 -translate-y-1/2",
+                        left: "{control.position.x * 100.0}%",
+                        top: "{control.position.y * 100.0}%",
+                        transform: "scale({control.scale})",
+
+                        // The real control, inert while editing
+                        div { class: "pointer-events-none opacity-60",
+                            LayoutControl { control: control.clone(), preview: true }
+                        }
+
+                        div {
+                            class: format!("absolute -inset-2 rounded-3xl cursor-move {}",
+                                if selected() == Some(i) { "ring-2 ring-pink-500" } else { "ring-1 ring-white/30" }),
+                            onpointerdown: move |evt| {
+                                evt.stop_propagation();
+                                let point = evt.client_coordinates();
+                                selected.set(Some(i));
+                                drag.set(Some((i, Drag::Move { start: (point.x, point.y), from: control.position })));
+                            },
+
+                            div {
+                                class: "absolute -right-3 -bottom-3 size-6 rounded-full bg-pink-500 cursor-nwse-resize",
+                                onpointerdown: move |evt| {
+                                    evt.stop_propagation();
+                                    let point = evt.client_coordinates();
+                                    selected.set(Some(i));
+                                    drag.set(Some((i, Drag::Resize { start: (point.x, point.y), from: control.scale })));
+                                },
+                            }
+                        }
+                    }
+                }
+
+                if let Some(index) = selected() {
+                    Inspector { draft, index, ondelete: move |_| selected.set(None) }
+                }
+            }
+
+            datalist { id: "known-actions",
+                for action in MOMENTARY_ACTIONS.into_iter().chain(SETTINGS.iter().map(|x| x.action)) {
+                    option { value: action }
+                }
+            }
+        }
+    }
+}
+
+// --- COMPONENT: Properties of the selected control ---
+#[component]
+fn Inspector(draft: Signal<Layout>, index: usize, ondelete: EventHandler) -> Element {
+    let Some(control) = draft.read().controls.get(index).cloned() else {
+        return VNode::empty();
+    };
+    let mut edit = move |change: Edit| {
+        change(&mut draft.write().controls[index]);
+    };
+
+    rsx! {
+        div {
+            class: "absolute top-4 right-4 w-72 max-h-[calc(100%-2rem)] overflow-y-auto touch-pan-y glass-panel rounded-3xl p-6 flex flex-col gap-4 z-30",
+            onpointerdown: move |evt| evt.stop_propagation(),
+
+            TextField {
+                label: "Title",
+                value: control.title.clone(),
+                onchange: move |x| edit(Box::new(move |c| c.title = x)),
+            }
+
+            match control.kind.clone() {
+                ControlKind::Analog { commands } => rsx! {
+                    Bindings { commands, dirs: vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right], edit }
+                },
+                ControlKind::Lever { commands, angle, hold } => rsx! {
+                    Bindings {
+                        commands,
+                        dirs: vec![Direction::from_angle(angle), Direction::from_angle(angle + 180.0)],
+                        edit,
+                    }
+                    NumberField {
+                        label: "Angle (° clockwise)",
+                        value: angle as i32,
+                        onchange: move |x: i32| edit(Box::new(move |c| {
+                            if let ControlKind::Lever { angle, .. } = &mut c.kind {
+                                *angle = x.into();
+                            }
+                        })),
+                    }
+                    label { class: "flex items-center gap-2 text-sm font-bold text-white/60",
+                        input {
+                            r#type: "checkbox",
+                            class: "accent-pink-500",
+                            checked: hold,
+                            onchange: move |evt| {
+                                let checked = evt.checked();
+                                edit(Box::new(move |c| {
+                                    if let ControlKind::Lever { hold, .. } = &mut c.kind {
+                                        *hold = checked;
+                                    }
+                                }));
+                            },
+                        }
+                        "Hold position when let go"
+                    }
+                },
+                ControlKind::Slider { command, min, max, step, initial } => rsx! {
+                    TextField {
+                        label: "Command",
+                        value: command,
+                        command: true,
+                        onchange: move |x| edit(Box::new(move |c| {
+                            if let ControlKind::Slider { command, .. } = &mut c.kind {
+                                *command = x;
+                            }
+                        })),
+                    }
+                    for (label, value, slot) in [("Min", min, 0), ("Max", max, 1), ("Step", step, 2), ("Initial", initial, 3)] {
+                        NumberField {
+                            label,
+                            value,
+                            onchange: move |x| edit(Box::new(move |c| {
+                                if let ControlKind::Slider { min, max, step, initial, .. } = &mut c.kind {
+                                    *[min, max, step, initial][slot] = x;
+                                }
+                            })),
+                        }
+                    }
+                },
+                ControlKind::Button { command, mode } => rsx! {
+                    TextField {
+                        label: "Command",
+                        value: command,
+                        command: true,
+                        onchange: move |x| edit(Box::new(move |c| {
+                            if let ControlKind::Button { command, .. } = &mut c.kind {
+                                *command = x;
+                            }
+                        })),
+                    }
+                    div { class: "glass-panel flex p-1 rounded-full",
+                        for option in ButtonMode::ALL {
+                            button {
+                                class: format!("flex-1 px-2 py-1 rounded-full text-xs font-bold transition-all {}",
+                                    if mode == option { "bg-white/20 text-white shadow-inner" } else { "text-white/40 hover:text-white/60" }),
+                                onclick: move |_| edit(Box::new(move |c| {
+                                    if let ControlKind::Button { mode, .. } = &mut c.kind {
+                                        *mode = option;
+                                    }
+                                })),
+                                "{option.label()}"
+                            }
+                        }
+                    }
+                },
+            }
+
+            if let Some(problem) = control.problem() {
+                p { class: "text-sm text-red-400", "Can't work: {problem}" }
+            }
+
+            div { class: "flex flex-col gap-1",
+                span { class: "text-xs font-bold text-white/40 uppercase", "Size {control.scale * 100.0:.0}%" }
+                input {
+                    r#type: "range",
+                    min: MIN_SCALE,
+                    max: MAX_SCALE,
+                    step: 0.05,
+                    value: "{control.scale}",
+                    class: "w-full h-2 bg-slate-700/50 rounded-lg appearance-none cursor-pointer accent-pink-500",
+                    oninput: move |evt| {
+                        if let Ok(scale) = evt.value().parse::<f64>() {
+                            edit(Box::new(move |c| c.scale = scale));
+                        }
+                    },
+                }
+            }
+
+            button {
+                class: "px-4 py-2 rounded-full text-sm font-bold text-red-400 hover:bg-red-500/20 transition-all",
+                onclick: move |_| {
+                    draft.write().controls.remove(index);
+                    ondelete.call(());
+                },
+                "Remove control"
+            }
+        }
+    }
+}
+
+/// Command fields for the directions a stick can send.
+#[component]
+fn Bindings(commands: Directions, dirs: Vec<Direction>, edit: Callback<Edit>) -> Element {
+    rsx! {
+        for dir in dirs {
+            TextField {
+                label: dir.name(),
+                value: commands.get(dir).unwrap_or_default().to_owned(),
+                command: true,
+                onchange: move |x: String| edit(Box::new(move |c| {
+                    if let ControlKind::Analog { commands } | ControlKind::Lever { commands, .. } = &mut c.kind
+                        && let Some(binding) = commands.get_mut(dir)
+                    {
+                        *binding = (!x.is_empty()).then_some(x);
+                    }
+                })),
+            }
+        }
+    }
+}
+
+#[component]
+fn TextField(
+    label: &'static str,
+    value: String,
+    onchange: EventHandler<String>,
+    /// Offer the known actions as completions.
+    #[props(default)]
+    command: bool,
+) -> Element {
+    rsx! {
+        label { class: "flex flex-col gap-1",
+            span { class: "text-xs font-bold text-white/40 uppercase", "{label}" }
+            input {
+                class: "bg-transparent border-b border-white/20 focus:border-pink-500 outline-none py-1 font-mono select-text",
+                list: if command { "known-actions" } else { "" },
+                value: "{value}",
+                onchange: move |evt| onchange.call(evt.value()),
+            }
+        }
+    }
+}
+
+#[component]
+fn NumberField(label: &'static str, value: i32, onchange: EventHandler<i32>) -> Element {
+    rsx! {
+        label { class: "flex flex-col gap-1",
+            span { class: "text-xs font-bold text-white/40 uppercase", "{label}" }
+            input {
+                class: "bg-transparent border-b border-white/20 focus:border-pink-500 outline-none py-1 font-mono select-text",
+                r#type: "number",
+                value: "{value}",
+                onchange: move |evt| {
+                    if let Ok(val) = evt.value().parse() {
+                        onchange.call(val);
+                    }
+                },
+            }
+        }
+    }
+}
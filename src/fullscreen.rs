@@ -0,0 +1,113 @@
+//! Fullscreen state, kept in sync when the browser or OS leaves fullscreen on its own
+//! (Escape, a swipe, F11).
+//!
+//! Web and mobile builds use the Fullscreen API, WebKit-prefixed where needed; desktop builds
+//! make the native window fullscreen instead.
+
+use dioxus::prelude::*;
+
+static FULLSCREEN: GlobalSignal<bool> = Signal::global(|| false);
+
+#[cfg(not(feature = "desktop"))]
+mod platform {
+    use dioxus::prelude::*;
+
+    use super::FULLSCREEN;
+
+    const WATCH: &str = r"
+        const current = () => !!(document.fullscreenElement || document.webkitFullscreenElement);
+        const report = () => dioxus.send(current());
+        document.addEventListener('fullscreenchange', report);
+        document.addEventListener('webkitfullscreenchange', report);
+        report();
+        await new Promise(() => {});
+    ";
+
+    const SET: &str = r"
+        const on = await dioxus.recv();
+        const elem = document.documentElement;
+        try {
+            if (on) {
+                await (elem.requestFullscreen?.() ?? elem.webkitRequestFullscreen?.());
+                // Browsers only allow locking the orientation while fullscreen
+                await screen.orientation?.lock?.('landscape');
+            } else {
+                screen.orientation?.unlock?.();
+                await (document.exitFullscreen?.() ?? document.webkitExitFullscreen?.());
+            }
+        } catch (err) {
+            console.warn('Fullscreen change refused', err);
+        }
+    ";
+
+    pub fn use_fullscreen() {
+        use_hook(|| {
+            let mut eval = document::eval(WATCH);
+            spawn(async move {
+                while let Ok(fullscreen) = eval.recv().await {
+                    *FULLSCREEN.write() = fullscreen;
+                }
+            });
+        });
+    }
+
+    /// The `fullscreenchange` listener reports back once the browser has switched.
+    pub fn set(fullscreen: bool) {
+        if let Err(err) = document::eval(SET).send(fullscreen) {
+            warn!("Failed to toggle fullscreen: {err}");
+        }
+    }
+}
+
+#[cfg(feature = "desktop")]
+mod platform {
+    use dioxus::{
+        desktop::{WindowEvent, tao::event::Event, use_wry_event_handler, window},
+        prelude::*,
+    };
+
+    use super::FULLSCREEN;
+
+    pub fn use_fullscreen() {
+        use_hook(|| *FULLSCREEN.write() = window().fullscreen().is_some());
+        use_wry_event_handler(|event, _| {
+            if let Event::WindowEvent {
+                event: WindowEvent::Resized(_),
+                ..
+            } = event
+            {
+                let fullscreen = window().fullscreen().is_some();
+                if *FULLSCREEN.peek() != fullscreen {
+                    *FULLSCREEN.write() = fullscreen;
+                }
+            }
+        });
+    }
+
+    pub fn set(fullscreen: bool) {
+        window().set_fullscreen(fullscreen);
+        *FULLSCREEN.write() = fullscreen;
+    }
+}
+
+/// Keeps the fullscreen state current; call once near the root.
+pub use platform::use_fullscreen;
+
+// --- COMPONENT: Fullscreen toggle for the header ---
+#[component]
+pub fn FullscreenButton() -> Element {
+    let (icon, hint) = if FULLSCREEN() {
+        ("⤡", "Exit fullscreen")
+    } else {
+        ("⛶", "Enter fullscreen")
+    };
+
+    rsx! {
+        button {
+            class: "group flex items-center justify-center p-3 rounded-xl glass-panel hover:bg-white/20 transition-all duration-300",
+            title: hint,
+            onclick: move |_| platform::set(!FULLSCREEN()),
+            span { class: "text-white/80 group-hover:text-white font-bold text-lg", "{icon}" }
+        }
+    }
+}
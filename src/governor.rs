@@ -0,0 +1,225 @@
+//! A speed limit for new drivers, applied to every command on its way to the robot.
+//!
+//! [`govern`] runs inside [`try_send`], so every input, from the touch controls to macros and
+//! scripts, is held to it. The limit scales the values of analog controls and caps the PWM duty
+//! cycle, which sets the speed of everything pressed. Beginner mode caps the speed further and
+//! keeps the lift and arm out of reach.
+//!
+//! Until the saved limit has loaded nothing but releases is sent, and the capped duty cycle is
+//! sent to every robot selected, so a saved limit holds from the first press.
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ROBOT, auth, capabilities,
+    command::{Command, MOMENTARY_ACTIONS, PWM_PERCENTAGE, Status, try_send},
+    settings, storage,
+};
+
+const STORAGE_KEY: &str = "governor";
+/// The speed limit in beginner mode, unless the set one is lower.
+const BEGINNER_LIMIT: i32 = 50;
+/// Actions beginner mode doesn't allow.
+const ADVANCED_ACTIONS: [&str; 4] = ["pull_up", "pull_down", "arm_up", "arm_down"];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Governor {
+    /// Top speed, in percent.
+    limit: i32,
+    beginner: bool,
+}
+
+impl Default for Governor {
+    fn default() -> Self {
+        Self {
+            limit: 100,
+            beginner: false,
+        }
+    }
+}
+
+impl Governor {
+    /// The top speed in effect, in percent.
+    const fn top_speed(self) -> i32 {
+        if self.beginner && self.limit > BEGINNER_LIMIT {
+            BEGINNER_LIMIT
+        } else {
+            self.limit
+        }
+    }
+
+    /// `command` as the robot may receive it, or why it may not.
+    fn apply(self, mut command: Command) -> Result<Command, String> {
+        // Letting go always gets through, or a press from before the limit couldn't end
+        if command.status == Status::Released {
+            return Ok(command);
+        }
+        let action = &*command.action;
+        if self.beginner && ADVANCED_ACTIONS.contains(&action) {
+            return Err(format!("beginner mode doesn't allow `{action}`"));
+        }
+        let top_speed = self.top_speed();
+        if let Status::Value(x) = &mut command.status {
+            if MOMENTARY_ACTIONS.contains(&action) {
+                *x = (f64::from(*x) * f64::from(top_speed) / 100.0).round() as i32;
+            } else if action == PWM_PERCENTAGE.action {
+                *x = (*x).min(top_speed.max(PWM_PERCENTAGE.min));
+            }
+        }
+        Ok(command)
+    }
+}
+
+static GOVERNOR: GlobalSignal<Governor> = Signal::global(Governor::default);
+
+/// Set once the saved limit, if any, is in [`GOVERNOR`].
+static LOADED: GlobalSignal<bool> = Signal::global(|| false);
+
+/// Whether the saved limit, if any, is in effect.
+pub fn loaded() -> bool {
+    LOADED()
+}
+
+/// `command` limited to the set speed, or why it isn't allowed at all.
+pub fn govern(command: Command) -> Result<Command, String> {
+    if !*LOADED.peek() && command.status != Status::Released {
+        return Err("the speed limit hasn't loaded yet".to_owned());
+    }
+    GOVERNOR.peek().apply(command)
+}
+
+/// Sends the PWM duty cycle again, so the robot runs at the capped speed right away.
+fn enforce() {
+    // Read in the task, so the effect calling this doesn't rerun on every slider move
+    spawn(async {
+        let limits = capabilities::limits(PWM_PERCENTAGE);
+        let _ = try_send(limits.command(settings::value(&limits))).await;
+    });
+}
+
+/// Changes the governor, applying a new cap at once.
+fn update(edit: impl FnOnce(&mut Governor)) {
+    let before = GOVERNOR.peek().top_speed();
+    edit(&mut GOVERNOR.write());
+    storage::save(STORAGE_KEY, &*GOVERNOR.peek());
+    if GOVERNOR.peek().top_speed() != before {
+        enforce();
+    }
+}
+
+/// Loads the speed limit at startup, and caps every robot selected from then on.
+pub fn use_governor() {
+    use_hook(|| {
+        spawn(async {
+            if let Some(saved) = storage::load(STORAGE_KEY).await {
+                *GOVERNOR.write() = saved;
+            }
+            *LOADED.write() = true;
+        })
+    });
+    use_effect(|| {
+        // A robot just picked, or driven before a reload, still runs at its own duty cycle
+        ROBOT.read();
+        if LOADED() && auth::loaded() && GOVERNOR.peek().top_speed() < 100 {
+            enforce();
+        }
+    });
+}
+
+// --- COMPONENT: Speed limit reminder for the header ---
+#[component]
+pub fn GovernorBadge() -> Element {
+    let governor = GOVERNOR();
+    if governor.top_speed() >= 100 && !governor.beginner {
+        return VNode::empty();
+    }
+
+    rsx! {
+        span {
+            class: "px-4 py-3 rounded-xl glass-panel text-sm font-bold text-emerald-300",
+            title: "Commands are limited in Settings",
+            if governor.beginner { "🐢 Beginner {governor.top_speed()}%" } else { "🐢 {governor.top_speed()}%" }
+        }
+    }
+}
+
+// --- COMPONENT: Speed limit for the Settings tab ---
+#[component]
+pub fn GovernorPanel() -> Element {
+    let governor = GOVERNOR();
+
+    rsx! {
+        div { class: "glass-border-gradient !rounded-3xl w-full",
+            div { class: "glass-panel p-8 rounded-3xl flex flex-col gap-6",
+                div { class: "flex justify-between items-end",
+                    div {
+                        h3 { class: "text-xl font-bold text-white", "Speed Limit" }
+                        p { class: "text-sm text-white/40", "Caps every command, whichever control or macro sends it" }
+                    }
+                    span { class: "text-4xl font-mono font-bold text-pink-500", "{governor.top_speed()}%" }
+                }
+                input {
+                    r#type: "range",
+                    min: PWM_PERCENTAGE.min,
+                    max: 100,
+                    step: 5,
+                    value: governor.limit,
+                    class: "w-full h-2 bg-slate-700/50 rounded-lg appearance-none cursor-pointer accent-pink-500 hover:accent-pink-400 transition-all",
+                    oninput: move |evt| {
+                        if let Ok(limit) = evt.value().parse() {
+                            update(|x| x.limit = limit);
+                        }
+                    },
+                }
+                label { class: "flex justify-between items-center gap-4",
+                    div {
+                        p { class: "text-sm font-bold text-white/60", "Beginner mode" }
+                        p { class: "text-xs text-white/40",
+                            "Drive at up to {BEGINNER_LIMIT}%, with the lift and arm locked"
+                        }
+                    }
+                    input {
+                        r#type: "checkbox",
+                        class: "size-5 accent-pink-500",
+                        checked: governor.beginner,
+                        onchange: move |evt| update(|x| x.beginner = evt.checked()),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF: Governor = Governor {
+        limit: 50,
+        beginner: false,
+    };
+
+    #[test]
+    fn scales_analog_values_and_caps_pwm() {
+        let lever = Command::new("pull_up", Status::Value(80));
+        assert_eq!(HALF.apply(lever).unwrap().status, Status::Value(40));
+        let pwm = Command::new(PWM_PERCENTAGE.action, Status::Value(90));
+        assert_eq!(HALF.apply(pwm).unwrap().status, Status::Value(50));
+        let blink = Command::new("blink_rate", Status::Value(150));
+        assert_eq!(HALF.apply(blink).unwrap().status, Status::Value(150));
+    }
+
+    #[test]
+    fn beginners_drive_slowly_without_the_arm() {
+        let beginner = Governor {
+            limit: 100,
+            beginner: true,
+        };
+        assert_eq!(beginner.top_speed(), BEGINNER_LIMIT);
+        assert!(beginner.apply(Command::pressed("arm_up")).is_err());
+        assert!(beginner.apply(Command::released("arm_up")).is_ok());
+        assert!(beginner.apply(Command::pressed("go_front")).is_ok());
+    }
+}
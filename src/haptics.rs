@@ -0,0 +1,200 @@
+//! Short vibrations so the driver can feel the sticks engage without looking at them.
+//!
+//! Uses the Vibration API, which also covers the Android webview on mobile as long as the app
+//! holds the `VIBRATE` permission. iOS webviews lack it, so the iOS build plays the closest
+//! native feedback instead. Desktops simply stay still.
+
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{storage, time::Instant};
+
+const STORAGE_KEY: &str = "haptics";
+/// Failures closer together than this, like those of a held control, buzz once.
+const ALERT_EVERY: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pulse {
+    /// A stick left its center.
+    Engage,
+    /// A stick reached the end of its travel.
+    Limit,
+    /// The robot rejected a command or couldn't be reached.
+    Alert,
+}
+
+impl Pulse {
+    const ALL: [Self; 3] = [Self::Engage, Self::Limit, Self::Alert];
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Engage => "Stick engaged",
+            Self::Limit => "End of travel",
+            Self::Alert => "Errors",
+        }
+    }
+
+    const fn details(self) -> &'static str {
+        match self {
+            Self::Engage => "When a stick leaves its center",
+            Self::Limit => "When a stick is pushed all the way",
+            Self::Alert => "When a command fails",
+        }
+    }
+
+    /// Alternating vibration and pause lengths, in milliseconds.
+    #[cfg(not(target_os = "ios"))]
+    const fn pattern(self) -> &'static [u32] {
+        match self {
+            Self::Engage => &[15],
+            Self::Limit => &[40],
+            Self::Alert => &[120, 60, 120, 60, 120],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HapticSettings {
+    engage: bool,
+    limit: bool,
+    alert: bool,
+}
+
+impl Default for HapticSettings {
+    fn default() -> Self {
+        Self {
+            engage: true,
+            limit: true,
+            alert: true,
+        }
+    }
+}
+
+impl HapticSettings {
+    const fn allows(mut self, pulse: Pulse) -> bool {
+        *self.toggle(pulse)
+    }
+
+    const fn toggle(&mut self, pulse: Pulse) -> &mut bool {
+        match pulse {
+            Pulse::Engage => &mut self.engage,
+            Pulse::Limit => &mut self.limit,
+            Pulse::Alert => &mut self.alert,
+        }
+    }
+}
+
+static SETTINGS: GlobalSignal<HapticSettings> = Signal::global(HapticSettings::default);
+/// When the last alert was felt.
+static LAST_ALERT: GlobalSignal<Option<Instant>> = Signal::global(|| None);
+
+/// Whether an alert at `now` should be felt, given when the last one was.
+fn alert_due(last: Option<Instant>, now: Instant) -> bool {
+    last.is_none_or(|x| now.duration_since(x) >= ALERT_EVERY)
+}
+
+pub fn pulse(pulse: Pulse) {
+    if !SETTINGS.peek().allows(pulse) {
+        return;
+    }
+    if pulse == Pulse::Alert {
+        let now = Instant::now();
+        if !alert_due(*LAST_ALERT.peek(), now) {
+            return;
+        }
+        *LAST_ALERT.write() = Some(now);
+    }
+    vibrate(pulse);
+}
+
+#[cfg(not(target_os = "ios"))]
+fn vibrate(pulse: Pulse) {
+    let eval = document::eval("navigator.vibrate?.(await dioxus.recv());");
+    if let Err(err) = eval.send(pulse.pattern()) {
+        warn!("Failed to vibrate: {err}");
+    }
+}
+
+#[cfg(target_os = "ios")]
+fn vibrate(pulse: Pulse) {
+    use objc2::MainThreadMarker;
+    use objc2_ui_kit::{
+        UIImpactFeedbackGenerator, UIImpactFeedbackStyle, UINotificationFeedbackGenerator,
+        UINotificationFeedbackType,
+    };
+
+    // UIKit may only be used from the main thread, where the UI runs
+    let Some(mtm) = MainThreadMarker::new() else {
+        warn!("Failed to vibrate: not on the main thread");
+        return;
+    };
+    let style = match pulse {
+        Pulse::Engage => UIImpactFeedbackStyle::Light,
+        Pulse::Limit => UIImpactFeedbackStyle::Heavy,
+        Pulse::Alert => {
+            UINotificationFeedbackGenerator::new(mtm)
+                .notificationOccurred(UINotificationFeedbackType::Error);
+            return;
+        }
+    };
+    // Its replacement is tied to a view, and the webview's isn't within reach
+    #[allow(deprecated)]
+    let generator = UIImpactFeedbackGenerator::initWithStyle(mtm.alloc(), style);
+    generator.impactOccurred();
+}
+
+// --- COMPONENT: Haptics for the Settings tab ---
+#[component]
+pub fn HapticsPanel() -> Element {
+    use_hook(|| {
+        spawn(async {
+            if let Some(saved) = storage::load(STORAGE_KEY).await {
+                *SETTINGS.write() = saved;
+            }
+        })
+    });
+
+    rsx! {
+        div { class: "glass-border-gradient !rounded-3xl w-full",
+            div { class: "glass-panel p-8 rounded-3xl flex flex-col gap-6",
+                div {
+                    h3 { class: "text-xl font-bold text-white", "Haptics" }
+                    p { class: "text-sm text-white/40", "Vibrate on supported devices" }
+                }
+                for pulse in Pulse::ALL {
+                    label { class: "flex justify-between items-center gap-4",
+                        div {
+                            p { class: "text-sm font-bold text-white/60", "{pulse.label()}" }
+                            p { class: "text-xs text-white/40", "{pulse.details()}" }
+                        }
+                        input {
+                            r#type: "checkbox",
+                            class: "size-5 accent-pink-500",
+                            checked: SETTINGS().allows(pulse),
+                            onchange: move |evt| {
+                                *SETTINGS.write().toggle(pulse) = evt.checked();
+                                storage::save(STORAGE_KEY, &*SETTINGS.peek());
+                            },
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_failures_buzz_once() {
+        let start = Instant::now();
+        assert!(alert_due(None, start));
+        assert!(!alert_due(Some(start), start + Duration::from_millis(250)));
+        assert!(alert_due(Some(start), start + ALERT_EVERY));
+    }
+}
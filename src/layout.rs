@@ -0,0 +1,401 @@
+//! The Control tab described as data, so each robot can bring its own control surface.
+//!
+//! A layout is a list of controls, each with a title, a `type`, the commands it sends and where
+//! it sits. Robots serve theirs as JSON from `/layout` and accept edited ones `POST`ed back;
+//! robots that don't get the bundled [`DEFAULT_LAYOUT`]. A layout edited on this device and
+//! saved locally takes precedence over both.
+
+use dioxus::prelude::*;
+use reqwasm::http::Request;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ActionButton, AnalogJoystick, AxisJoystick, ButtonMode, CustomSlider, Direction, ROBOT, auth,
+    capabilities::{self, SettingLimits},
+    command::{Command, SETTINGS},
+    storage,
+    units::Unit,
+};
+
+const DEFAULT_LAYOUT: &str = include_str!("../assets/layouts/default.json");
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Layout {
+    pub controls: Vec<Control>,
+}
+
+impl Layout {
+    pub fn bundled() -> Self {
+        serde_json::from_str(DEFAULT_LAYOUT).expect("the bundled layout should be valid")
+    }
+
+    /// The layout without controls that can't work, like sliders with `min` above `max`.
+    fn checked(mut self) -> Self {
+        self.controls.retain(|x| match x.problem() {
+            Some(problem) => {
+                warn!("Ignoring the control `{}`: {problem}", x.title);
+                false
+            }
+            None => true,
+        });
+        self
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Control {
+    pub title: String,
+    #[serde(flatten)]
+    pub kind: ControlKind,
+    pub position: Position,
+    /// Size relative to the control's natural size.
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+}
+
+impl Control {
+    /// Why the control can't work, if it can't.
+    pub fn problem(&self) -> Option<&'static str> {
+        match &self.kind {
+            ControlKind::Slider {
+                command,
+                min,
+                max,
+                step,
+                initial,
+            } => slider_limits(command.clone(), *min, *max, *step, *initial).problem(),
+            _ => None,
+        }
+    }
+
+    /// Tells controls apart, so one taking another's place in a new layout starts afresh
+    /// instead of keeping its state, like its tuning and whether it's held.
+    pub fn key(&self, index: usize) -> String {
+        let kind = serde_json::to_string(&self.kind).unwrap_or_default();
+        format!("{index}/{}/{kind}", self.title)
+    }
+}
+
+const fn default_scale() -> f64 {
+    1.0
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlKind {
+    /// 360° stick snapping to whichever of its four directions is pushed hardest.
+    Analog { commands: Directions },
+    /// Single-axis stick tilted `angle` degrees clockwise from vertical. Each end sends the
+    /// command for the direction it points closest to; the others are ignored.
+    #[serde(alias = "vertical")]
+    Lever {
+        commands: Directions,
+        #[serde(default)]
+        angle: f64,
+        /// Stays where it's let go like a throttle, sending its deflection as a value, instead
+        /// of springing back to the center.
+        #[serde(default)]
+        hold: bool,
+    },
+    /// Sends its value as `command` whenever it's moved.
+    Slider {
+        command: String,
+        min: i32,
+        max: i32,
+        step: i32,
+        initial: i32,
+    },
+    /// Sends `command` the way its `mode` says, momentary unless given.
+    Button {
+        command: String,
+        #[serde(default)]
+        mode: ButtonMode,
+    },
+}
+
+/// Momentary actions sent per direction; directions without one do nothing.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Directions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub up: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub down: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub left: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub right: Option<String>,
+}
+
+impl Directions {
+    pub fn get(&self, dir: Direction) -> Option<&str> {
+        match dir {
+            Direction::Up => self.up.as_deref(),
+            Direction::Down => self.down.as_deref(),
+            Direction::Left => self.left.as_deref(),
+            Direction::Right => self.right.as_deref(),
+            Direction::Neutral => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, dir: Direction) -> Option<&mut Option<String>> {
+        match dir {
+            Direction::Up => Some(&mut self.up),
+            Direction::Down => Some(&mut self.down),
+            Direction::Left => Some(&mut self.left),
+            Direction::Right => Some(&mut self.right),
+            Direction::Neutral => None,
+        }
+    }
+}
+
+/// The limits of a slider bound to an action that isn't a setting.
+fn slider_limits(action: String, min: i32, max: i32, step: i32, initial: i32) -> SettingLimits {
+    SettingLimits {
+        action,
+        min,
+        max,
+        step,
+        unit: Unit::Plain,
+        wire: None,
+        default: initial,
+        log_scale: false,
+        safe: None,
+    }
+}
+
+/// Center of the control, as fractions of the control area's width and height.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+pub static LAYOUT: GlobalSignal<Layout> = Signal::global(Layout::bundled);
+
+/// Local layouts are kept per robot, since each robot has its own commands.
+fn storage_key() -> String {
+    format!("layout/{}", ROBOT.peek().name)
+}
+
+pub fn save_local(layout: &Layout) {
+    storage::save(&storage_key(), layout);
+}
+
+pub fn forget_local() {
+    storage::remove(&storage_key());
+}
+
+async fn fetch_layout(base_url: &str) -> Option<Layout> {
+    let resp = auth::authorize(Request::get(&format!("{base_url}/layout")))
+        .send()
+        .await
+        .ok()?;
+    if resp.status() != 200 {
+        return None;
+    }
+    resp.json()
+        .await
+        .inspect_err(|err| warn!("Ignoring the robot's layout: {err}"))
+        .ok()
+        .map(Layout::checked)
+}
+
+pub async fn upload(layout: &Layout) -> Result<(), String> {
+    let body = serde_json::to_string(layout).map_err(|err| err.to_string())?;
+    let resp = auth::authorize(Request::post(&format!("{}/layout", ROBOT.peek().base_url)))
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+    if let Some(error) = auth::check(&resp).await {
+        return Err(error.to_string());
+    }
+    match resp.status() {
+        200 => Ok(()),
+        code => Err(format!("the robot answered {code}")),
+    }
+}
+
+/// The layout this device should show: saved locally, else the robot's own, else the bundled one.
+pub async fn load() -> Layout {
+    let key = storage_key();
+    if let Some(saved) = storage::load::<Layout>(&key).await {
+        return saved.checked();
+    }
+    let base_url = ROBOT.peek().base_url.clone();
+    fetch_layout(&base_url)
+        .await
+        .unwrap_or_else(Layout::bundled)
+}
+
+/// Switches to the selected robot's layout whenever the robot changes, once paired.
+pub fn use_robot_layout() {
+    use_effect(|| {
+        // `loaded` subscribes the effect to robot changes; `load` only peeks.
+        if !auth::loaded() {
+            return;
+        }
+        spawn(async move {
+            *LAYOUT.write() = load().await;
+        });
+    });
+}
+
+// --- COMPONENT: Renders the current layout ---
+#[component]
+pub fn ControlSurface() -> Element {
+    rsx! {
+        for (i, control) in LAYOUT().controls.into_iter().enumerate() {
+            div {
+                key: "{control.key(i)}",
+                class: "absolute -translate-x-1/2 -translate-y-1/2 z-10",
+                left: "{control.position.x * 100.0}%",
+                top: "{control.position.y * 100.0}%",
+                transform: "scale({control.scale})",
+                LayoutControl { control: control.clone() }
+            }
+        }
+    }
+}
+
+/// One control of a layout. A `preview` is shown in the editor rather than driven.
+#[component]
+pub fn LayoutControl(control: Control, #[props(default)] preview: bool) -> Element {
+    let title = control.title;
+    match control.kind {
+        ControlKind::Analog { commands } => rsx! {
+            LayoutJoystick { title, commands }
+        },
+        ControlKind::Lever {
+            commands,
+            angle,
+            hold,
+        } => rsx! {
+            LayoutJoystick { title, commands, angle, hold, preview }
+        },
+        ControlKind::Slider {
+            command,
+            min,
+            max,
+            step,
+            initial,
+        } => {
+            // Sliders for the robot's own settings take its limits, safe range included
+            let setting = match SETTINGS.into_iter().find(|x| x.action == command) {
+                Some(x) => capabilities::limits(x),
+                None => slider_limits(command, min, max, step, initial),
+            };
+            rsx! {
+                div { class: "w-96",
+                    CustomSlider { title, details: "", setting }
+                }
+            }
+        }
+        ControlKind::Button { command, mode } => rsx! {
+            ActionButton { title, action: command, mode }
+        },
+    }
+}
+
+/// An analog stick, or a lever when given an `angle`.
+#[component]
+fn LayoutJoystick(
+    title: String,
+    commands: Directions,
+    angle: Option<f64>,
+    #[props(default)] hold: bool,
+    #[props(default)] preview: bool,
+) -> Element {
+    // Directions the firmware doesn't understand act as if unbound
+    let (press, release) = (commands.clone(), commands);
+    let press_cmd = use_callback(move |dir| {
+        press
+            .get(dir)
+            .filter(|x| capabilities::supports(x))
+            .map(|x| Command::pressed(x.to_owned()))
+    });
+    let release_cmd = use_callback(move |dir| {
+        release
+            .get(dir)
+            .filter(|x| capabilities::supports(x))
+            .map(|x| Command::released(x.to_owned()))
+    });
+
+    match angle {
+        Some(angle) => rsx! {
+            AxisJoystick { title, press_cmd, release_cmd, angle, hold, preview }
+        },
+        None => rsx! { AnalogJoystick { title, press_cmd, release_cmd } },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_layout_parses() {
+        let layout = Layout::bundled();
+        assert_eq!(layout.controls.len(), 3);
+        // Held levers send values, which older firmware doesn't understand
+        assert!(
+            layout
+                .controls
+                .iter()
+                .all(|x| !matches!(x.kind, ControlKind::Lever { hold: true, .. }))
+        );
+    }
+
+    #[test]
+    fn vertical_controls_parse_as_upright_levers() {
+        let control: Control = serde_json::from_str(
+            r#"{ "title": "Lift", "type": "vertical", "commands": { "up": "pull_up" }, "position": { "x": 0.5, "y": 0.5 } }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            control.kind,
+            ControlKind::Lever {
+                angle: 0.0,
+                hold: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn drops_sliders_that_cant_work() {
+        let layout: Layout = serde_json::from_str(
+            r#"{ "controls": [
+                { "title": "Ok", "type": "slider", "command": "speed", "min": 0, "max": 10, "step": 1, "initial": 5, "position": { "x": 0.5, "y": 0.5 } },
+                { "title": "Upside down", "type": "slider", "command": "speed", "min": 10, "max": 0, "step": 1, "initial": 5, "position": { "x": 0.5, "y": 0.5 } },
+                { "title": "Stuck", "type": "slider", "command": "speed", "min": 0, "max": 10, "step": 0, "initial": 5, "position": { "x": 0.5, "y": 0.5 } }
+            ] }"#,
+        )
+        .unwrap();
+        let titles: Vec<_> = layout
+            .checked()
+            .controls
+            .into_iter()
+            .map(|x| x.title)
+            .collect();
+        assert_eq!(titles, ["Ok"]);
+    }
+
+    #[test]
+    fn controls_taking_anothers_place_get_their_own_key() {
+        let lift = &Layout::bundled().controls[1];
+        let mut held = lift.clone();
+        if let ControlKind::Lever { hold, .. } = &mut held.kind {
+            *hold = true;
+        }
+        let renamed = Control {
+            title: "Winch".to_owned(),
+            ..lift.clone()
+        };
+        assert_ne!(lift.key(0), held.key(0));
+        assert_ne!(lift.key(0), renamed.key(0));
+        assert_eq!(lift.key(0), lift.clone().key(0));
+    }
+}
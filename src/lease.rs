@@ -0,0 +1,207 @@
+//! One driver at a time: the robot leases control to a single client and the others spectate.
+//!
+//! Each device has a random client id. While the UI is open it renews its lease through
+//! `POST /lease` every [`HEARTBEAT`]; the robot drops a lease whose heartbeats stop and hands it
+//! to whoever asks next. Commands carry the client id so the robot can refuse spectators.
+//! Robots without `/lease` let everyone drive, as before.
+
+use std::time::Duration;
+
+use dioxus::{core::Task, prelude::*};
+use reqwasm::http::Request;
+use serde::{Deserialize, Serialize};
+
+use crate::{ROBOT, auth, command_log, storage, time};
+
+const HEARTBEAT: Duration = Duration::from_secs(1);
+const CLIENT_KEY: &str = "client_id";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum LeaseAction {
+    /// Keep the lease if held, take it if nobody holds it.
+    Renew,
+    /// Ask for the lease while someone else holds it.
+    Take,
+    /// Hand the lease over to whoever asked for it.
+    Release,
+}
+
+#[derive(Serialize)]
+struct LeaseRequest<'a> {
+    client: &'a str,
+    action: LeaseAction,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+struct LeaseStatus {
+    /// Client id of the driver, if anyone drives.
+    holder: Option<String>,
+    /// Client id of a spectator waiting for control.
+    #[serde(default)]
+    requested_by: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Role {
+    /// The robot doesn't arbitrate, or hasn't answered yet.
+    Unmanaged,
+    Driver {
+        requested: bool,
+    },
+    Spectator {
+        asked: bool,
+    },
+}
+
+static CLIENT: GlobalSignal<String> = Signal::global(String::new);
+static ROLE: GlobalSignal<Role> = Signal::global(|| Role::Unmanaged);
+/// The robot whose answer [`ROLE`] comes from, once it answered.
+static ANSWERED: GlobalSignal<Option<String>> = Signal::global(|| None);
+
+/// Whether the selected robot said who drives it, or that it doesn't arbitrate.
+pub fn known() -> bool {
+    ANSWERED.read().as_ref() == Some(&ROBOT.read().name)
+}
+
+/// Records that the selected robot answered, without waking effects on every heartbeat.
+fn answered() {
+    let robot = &ROBOT.peek().name;
+    if ANSWERED.peek().as_ref() != Some(robot) {
+        *ANSWERED.write() = Some(robot.clone());
+    }
+}
+
+/// Whether commands from this device would be accepted.
+pub fn may_drive() -> bool {
+    !matches!(*ROLE.peek(), Role::Spectator { .. })
+}
+
+/// Tags a request for the robot with this device's client id.
+pub fn identify(request: Request) -> Request {
+    request.header("X-Client-Id", &CLIENT.peek())
+}
+
+async fn load_client_id() -> String {
+    if let Some(id) = storage::load(CLIENT_KEY).await {
+        return id;
+    }
+    let id = document::eval("return crypto.randomUUID?.() ?? Math.random().toString(36).slice(2);")
+        .join::<String>()
+        .await
+        .unwrap_or_else(|_| command_log::unix_millis().to_string());
+    storage::save(CLIENT_KEY, &id);
+    id
+}
+
+/// Sends `action` and updates [`ROLE`] from the answer. Returns `false` if the robot doesn't
+/// arbitrate control at all.
+async fn exchange(action: LeaseAction) -> bool {
+    let client = CLIENT.peek().clone();
+    let Ok(body) = serde_json::to_string(&LeaseRequest {
+        client: &client,
+        action,
+    }) else {
+        return true;
+    };
+    let request = Request::post(&format!("{}/lease", ROBOT.peek().base_url))
+        .header("Content-Type", "application/json")
+        .body(body);
+    let resp = match auth::authorize(identify(request)).send().await {
+        Ok(x) => x,
+        Err(err) => {
+            // Keep the last known role through dropouts; the robot decides when it lapses
+            warn!("Lease heartbeat failed: {err}");
+            return true;
+        }
+    };
+    match resp.status() {
+        200 => {}
+        404 => {
+            *ROLE.write() = Role::Unmanaged;
+            answered();
+            return false;
+        }
+        code => {
+            auth::check(&resp).await;
+            warn!("Lease refused with {code}");
+            return true;
+        }
+    }
+    let Ok(status) = resp.json::<LeaseStatus>().await else {
+        return true;
+    };
+    let role = match status.holder {
+        Some(holder) if holder == client => Role::Driver {
+            requested: status.requested_by.is_some(),
+        },
+        Some(_) => Role::Spectator {
+            asked: status.requested_by.as_deref() == Some(&client),
+        },
+        None => Role::Unmanaged,
+    };
+    if *ROLE.peek() != role {
+        *ROLE.write() = role;
+    }
+    answered();
+    true
+}
+
+/// Keeps this device's lease alive for the selected robot while the UI is open.
+pub fn use_lease() {
+    let mut heartbeat = use_signal(|| None::<Task>);
+    use_effect(move || {
+        // Subscribes the effect to robot changes; the heartbeat only peeks.
+        ROBOT.read();
+        if let Some(task) = heartbeat.take() {
+            task.cancel();
+        }
+        *ROLE.write() = Role::Unmanaged;
+        if !auth::loaded() {
+            return;
+        }
+        heartbeat.set(Some(spawn(async move {
+            if CLIENT.peek().is_empty() {
+                *CLIENT.write() = load_client_id().await;
+            }
+            while exchange(LeaseAction::Renew).await {
+                time::sleep(HEARTBEAT).await;
+            }
+        })));
+    });
+}
+
+// --- COMPONENT: Lease state over the Control tab ---
+#[component]
+pub fn LeaseOverlay() -> Element {
+    match ROLE() {
+        Role::Unmanaged | Role::Driver { requested: false } => VNode::empty(),
+        Role::Driver { requested: true } => rsx! {
+            div { class: "absolute top-0 left-1/2 -translate-x-1/2 z-30 flex items-center gap-4 px-6 py-3 rounded-full glass-panel",
+                span { class: "text-sm font-bold text-yellow-300", "Another device asks for control" }
+                button {
+                    class: "px-4 py-1 rounded-full text-sm font-bold glass-panel hover:bg-white/20 transition-all",
+                    onclick: move |_| async move {
+                        exchange(LeaseAction::Release).await;
+                    },
+                    "Hand over"
+                }
+            }
+        },
+        Role::Spectator { asked } => rsx! {
+            // Covers the controls so they can't be touched
+            div { class: "absolute inset-0 z-30 flex flex-col items-center justify-center gap-6 rounded-3xl bg-slate-900/60 backdrop-blur-sm text-center",
+                p { class: "text-xl font-bold text-white", "Spectating" }
+                p { class: "text-sm text-white/40 max-w-72", "Another device is driving. Controls unlock when it hands over or goes quiet." }
+                button {
+                    class: "px-6 py-2 rounded-full text-sm font-bold glass-panel hover:bg-white/20 transition-all disabled:opacity-40",
+                    disabled: asked,
+                    onclick: move |_| async move {
+                        exchange(LeaseAction::Take).await;
+                    },
+                    if asked { "Control requested…" } else { "Request control" }
+                }
+            }
+        },
+    }
+}
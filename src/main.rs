@@ -1,11 +1,54 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display, time::Duration};
 
 use dioxus::{
-    core::{IntoAttributeValue, bail},
-    document::eval,
+    core::{IntoAttributeValue, Task, bail},
     prelude::*,
 };
-use reqwasm::http::Request;
+use serde::{Deserialize, Serialize};
+
+use auth::{PairingBadge, PairingPanel, SigningPanel, use_pairing};
+use capabilities::{CompatibilityBanner, SettingLimits};
+use command::{BLINK_RATE, Command, PWM_FREQUENCY, PWM_PERCENTAGE, SETTINGS, Status, send_command};
+use command_log::CommandLog;
+use editor::LayoutEditor;
+use fullscreen::{FullscreenButton, use_fullscreen};
+use governor::{GovernorBadge, GovernorPanel, use_governor};
+use haptics::{HapticsPanel, Pulse};
+use layout::{ControlSurface, use_robot_layout};
+use lease::{LeaseOverlay, use_lease};
+use pwa::{InstallButton, use_pwa};
+use recorder::{MacroBar, MacroList};
+use script::ScriptEditor;
+use settings::{SettingsToolbar, use_settings};
+use time::Instant;
+use tuning::{StickTuning, TuningPanel, use_tuning};
+use units::{Decimal, Unit};
+use wake_lock::{WakeLockIndicator, use_wake_lock};
+
+mod auth;
+mod capabilities;
+mod command;
+mod command_log;
+#[cfg(feature = "discovery")]
+mod discovery;
+mod editor;
+mod fullscreen;
+mod governor;
+mod haptics;
+mod layout;
+mod lease;
+mod pwa;
+mod recorder;
+mod script;
+mod settings;
+mod storage;
+mod time;
+mod tuning;
+mod units;
+mod wake_lock;
+
+#[cfg(feature = "discovery")]
+use discovery::RobotPicker;
 
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
 
@@ -23,26 +66,27 @@ macro_rules! twnum {
     };
 }
 
-// --- API Logic (Unchanged) ---
-async fn send_command((action, status): (&str, &str)) {
-    let params = format!("cmd={action}&status={status}");
-    let resp = Request::post("/controller")
-        .header("Content-Type", "application/x-www-form-urlencoded")
-        .body(&params)
-        .send()
-        .await;
+// --- Robot Selection ---
+#[derive(Clone, PartialEq, Debug)]
+struct Robot {
+    name: String,
+    firmware: String,
+    /// Prefixed to every request path; empty targets the origin that served the UI.
+    base_url: String,
+}
 
-    match resp {
-        Ok(x) if x.status() != 200 => {
-            error!("Failed: {} code: {}", params, x.status());
-        }
-        Err(err) => {
-            error!("Error: {} - {}", params, err);
+impl Robot {
+    /// The robot hosting this UI, which is the only one reachable from the web build.
+    fn origin() -> Self {
+        Self {
+            name: "robot-controller-v2".to_owned(),
+            firmware: "0.0.1-alpha.0".to_owned(),
+            base_url: String::new(),
         }
-        _ => {}
     }
 }
 
+static ROBOT: GlobalSignal<Robot> = Signal::global(Robot::origin);
 // --- STYLES: Animated Mesh Background & Glass Utilities ---
 const GLOBAL_STYLES: &str = r#"
     @keyframes blob {
@@ -114,39 +158,52 @@ const GLOBAL_STYLES: &str = r#"
 enum ActiveTab {
     Control,
     Settings,
+    Log,
 }
 
 #[component]
 fn Controller() -> Element {
     let mut active_tab = use_signal(|| ActiveTab::Control);
-    let toggle_fullscreen = move |_| {
-        let js_script = r"
-            var elem = document.documentElement;
-            if (!document.fullscreenElement) {
-                elem.requestFullscreen().catch(err => {});
-            } else {
-                document.exitFullscreen();
-            }
-        ";
-        let _ = eval(js_script);
-    };
+    let mut editing_layout = use_signal(|| false);
+    let mut portrait_dismissed = use_signal(|| false);
+    use_robot_layout();
+    use_pairing();
+    capabilities::use_capabilities();
+    use_settings();
+    use_governor();
+    use_lease();
+    use_pwa();
+    // Only driving needs the screen on
+    use_wake_lock(use_memo(move || active_tab() == ActiveTab::Control));
+    use_fullscreen();
 
     rsx! {
         document::Link { rel: "stylesheet", href: TAILWIND_CSS }
+        // Served unhashed from `public/`, since the service worker and manifest refer to them by name
+        document::Link { rel: "manifest", href: "/manifest.json" }
+        document::Link { rel: "apple-touch-icon", href: "/icon-192.png" }
+        document::Meta { name: "theme-color", content: "#0f172a" }
         style { "{GLOBAL_STYLES}" }
 
         // --- Main Container with Big Sur Style Background ---
         div { class: "relative flex not-sm:flex-col items-center justify-between h-screen w-screen overflow-hidden bg-slate-900 gap-10 p-6 sm:px-12 pt-24 touch-none select-none text-white",
+            // Any touch takes control back from a running macro
+            onpointerdown: move |_| recorder::abort(),
+
+            CompatibilityBanner {}
 
             // --- TOP HEADER BAR ---
             div { class: "absolute top-6 left-6 right-6 flex justify-between items-center z-50",
-                // Fullscreen Toggle
-                button {
-                    class: "group flex items-center justify-center p-3 rounded-xl glass-panel hover:bg-white/20 transition-all duration-300",
-                    onclick: toggle_fullscreen,
-                    span { class: "text-white/80 group-hover:text-white font-bold text-lg", "⛶" }
+                div { class: "flex items-center gap-2",
+                    FullscreenButton {}
+                    WakeLockIndicator {}
+                    InstallButton {}
+                    PairingBadge {}
+                    GovernorBadge {}
                 }
 
+                MacroBar {}
+
                 // Tab Selector
                 div { class: "glass-border-gradient !rounded-full",
                     div { class: "glass-panel flex p-1 rounded-full",
@@ -162,15 +219,32 @@ fn Controller() -> Element {
                             onclick: move |_| active_tab.set(ActiveTab::Settings),
                             "Settings"
                         }
+                        button {
+                            class: format!("px-6 py-2 rounded-full text-sm font-bold transition-all {}",
+                                if active_tab() == ActiveTab::Log { "bg-white/20 text-white shadow-inner" } else { "text-white/40 hover:text-white/60" }),
+                            onclick: move |_| active_tab.set(ActiveTab::Log),
+                            "Log"
+                        }
                     }
                 }
             }
 
 
+            div {
+                class: "w-full h-full flex flex-col items-center justify-start gap-6 touch-pan-y overflow-y-auto",
+                hidden: active_tab() != ActiveTab::Log,
+                h2 { class: "text-2xl font-light tracking-widest text-white/50 uppercase", "Command Log" }
+                // Only rendered while shown, or every command would redraw the whole log
+                if active_tab() == ActiveTab::Log {
+                    CommandLog {}
+                }
+            }
+
             div {
                 class: "w-full h-full flex flex-col items-center justify-start gap-8 touch-pan-y overflow-y-auto",
                 hidden: active_tab() != ActiveTab::Settings,
                 h2 { class: "text-2xl font-light tracking-widest text-white/50 uppercase", "System Settings" }
+                SettingsToolbar { settings: SETTINGS.map(capabilities::limits).to_vec() }
 
                 div {
                     class: "w-full max-w-xl",
@@ -185,53 +259,72 @@ fn Controller() -> Element {
                     FrequencySlider {}
                 }
 
-                div { class: "text-center opacity-20 text-xs mt-10",
-                    p { "Connected to: robot-controller-v2" }
-                    p { "Firmware: 0.0.1-alpha.0" }
+                div {
+                    class: "w-full max-w-xl flex flex-col gap-8",
+                    TuningPanel {}
+                }
+                div {
+                    class: "w-full max-w-xl",
+                    GovernorPanel {}
+                }
+                div {
+                    class: "w-full max-w-xl",
+                    HapticsPanel {}
                 }
-            }
 
-            button {
-                class: "absolute top-6 left-6 group flex items-center justify-center p-3 rounded-full glass-panel hover:bg-white/20 transition-all duration-300 z-50",
-                onclick: toggle_fullscreen,
-                span { class: "text-white/80 group-hover:text-white font-bold text-lg", "⛶" }
+                div {
+                    class: "w-full max-w-xl",
+                    MacroList {}
+                }
+                div {
+                    class: "w-full max-w-xl",
+                    ScriptEditor {}
+                }
+
+                div {
+                    class: "w-full max-w-xl",
+                    RobotPicker {}
+                }
+                div {
+                    class: "w-full max-w-xl",
+                    PairingPanel {}
+                }
+                div {
+                    class: "w-full max-w-xl",
+                    SigningPanel {}
+                }
+
+                div { class: "text-center opacity-20 text-xs mt-10",
+                    p { "Connected to: {ROBOT.read().name}" }
+                    p { "Firmware: {capabilities::firmware().unwrap_or_else(|| ROBOT.read().firmware.clone())}" }
+                }
             }
 
             div {
-                class: "contents",
+                class: "relative w-full h-full",
                 hidden: active_tab() != ActiveTab::Control,
-                div { class: "flex flex-col items-center gap-6 justify-self-start z-10",
-                    span { class: "text-white/60 font-bold uppercase tracking-[0.2em] text-sm drop-shadow-md", "Movement" }
-                    AnalogJoystick {}
-                }
-
-                div { class: "justify-self-end flex justify-end gap-12 z-10",
-                    VerticalJoystick {
-                        title: "Lift",
-                        press_cmd: |cmd| match cmd {
-                            Direction::Up => Some(("pull_up", "pressed")),
-                            Direction::Down => Some(("pull_down", "pressed")),
-                            _ => None,
-                        },
-                        release_cmd: |cmd| match cmd {
-                            Direction::Up => Some(("pull_up", "released")),
-                            Direction::Down => Some(("pull_down", "released")),
-                            _ => None,
+                if editing_layout() {
+                    LayoutEditor { ondone: move |_| editing_layout.set(false) }
+                } else {
+                    ControlSurface {}
+                    LeaseOverlay {}
+                    // Layouts are designed for landscape; upright, the controls would overlap
+                    if !portrait_dismissed() {
+                        div { class: "hidden portrait:flex absolute inset-0 z-40 flex-col items-center justify-center gap-6 rounded-3xl glass-panel text-center",
+                            span { class: "text-6xl text-white/80 animate-pulse", "⟳" }
+                            p { class: "text-xl font-bold text-white", "Rotate your device" }
+                            p { class: "text-sm text-white/40 max-w-60", "The controls are laid out for landscape" }
+                            button {
+                                class: "px-4 py-2 rounded-full text-sm font-bold text-white/40 hover:text-white/80 transition-all",
+                                onclick: move |_| portrait_dismissed.set(true),
+                                "Use anyway"
+                            }
                         }
                     }
-
-                    VerticalJoystick {
-                        title: "Arm",
-                        press_cmd: |cmd| match cmd {
-                            Direction::Up => Some(("arm_up", "pressed")),
-                            Direction::Down => Some(("arm_down", "pressed")),
-                            _ => None,
-                        },
-                        release_cmd: |cmd| match cmd {
-                            Direction::Up => Some(("arm_up", "released")),
-                            Direction::Down => Some(("arm_down", "released")),
-                            _ => None,
-                        }
+                    button {
+                        class: "absolute top-0 right-0 px-4 py-2 rounded-full text-sm font-bold glass-panel text-white/40 hover:text-white/80 hover:bg-white/20 transition-all z-20",
+                        onclick: move |_| editing_layout.set(true),
+                        "✎ Edit layout"
                     }
                 }
             }
@@ -249,15 +342,6 @@ enum Direction {
 }
 
 impl Direction {
-    const fn to_command(self) -> &'static str {
-        match self {
-            Self::Up => "go_front",
-            Self::Down => "go_back",
-            Self::Left => "turn_left",
-            Self::Right => "turn_right",
-            Self::Neutral => unreachable!(),
-        }
-    }
     fn icon(self) -> Result<&'static str> {
         Ok(match self {
             Self::Up => "↑",
@@ -267,45 +351,196 @@ impl Direction {
             Self::Neutral => bail!("ain't got an icon"),
         })
     }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Neutral => "Neutral",
+            Self::Up => "Up",
+            Self::Down => "Down",
+            Self::Left => "Left",
+            Self::Right => "Right",
+        }
+    }
+
+    /// The direction closest to `angle` degrees clockwise from straight up.
+    fn from_angle(angle: f64) -> Self {
+        match ((angle.rem_euclid(360.0) + 45.0) / 90.0) as u8 % 4 {
+            0 => Self::Up,
+            1 => Self::Right,
+            2 => Self::Down,
+            _ => Self::Left,
+        }
+    }
 }
 
-// --- COMPONENT: Vertical Slider ---
+/// Buzzes when a stick leaves its center or first reaches the end of its travel.
+fn feel_stick(from: Direction, to: Direction, previous_travel: f64, travel: f64, max_radius: f64) {
+    if from == Direction::Neutral && to != Direction::Neutral {
+        haptics::pulse(Pulse::Engage);
+    } else if previous_travel < max_radius && travel >= max_radius {
+        haptics::pulse(Pulse::Limit);
+    }
+}
+
+/// How often a held lever repeats its value, so the robot knows it's still meant.
+const HOLD_REPEAT: Duration = Duration::from_millis(250);
+/// Two taps closer together than this center a held lever.
+const DOUBLE_TAP: Duration = Duration::from_millis(300);
+
+/// Where a held lever was left.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct LeverState {
+    pos: f64,
+    output: f64,
+    dir: Direction,
+}
+
+/// Held levers by title, so they keep their position when the Control tab is rebuilt.
+static HELD_LEVERS: GlobalSignal<HashMap<String, LeverState>> = Signal::global(HashMap::new);
+
+// --- COMPONENT: Single-axis joystick ---
+/// A lever along one axis, `angle` degrees clockwise from vertical.
+///
+/// Its ends send whichever direction they point closest to, so a horizontal lever uses the
+/// left and right commands. With `hold` the puck stays where it's let go, like a throttle, and
+/// instead of pressing and releasing it keeps sending its end's command with the deflection
+/// in percent. Double-tap it to center.
+///
+/// A `preview`, like the layout editor's, neither picks up a held position nor repeats it.
 #[component]
-fn VerticalJoystick(
-    title: &'static str,
-    press_cmd: Callback<Direction, Option<(&'static str, &'static str)>>,
-    release_cmd: Callback<Direction, Option<(&'static str, &'static str)>>,
+fn AxisJoystick(
+    title: String,
+    press_cmd: Callback<Direction, Option<Command>>,
+    release_cmd: Callback<Direction, Option<Command>>,
+    #[props(default = StickTuning::new(60.0, 15.0))] tuning: StickTuning,
+    #[props(default)] angle: f64,
+    #[props(default)] hold: bool,
+    #[props(default)] preview: bool,
 ) -> Element {
-    let mut stick_pos = use_signal(|| 0.0f64);
+    // Only the driving surface keeps levers held; previews would send alongside it
+    let hold_state = hold && !preview;
+    let saved = use_hook(|| {
+        HELD_LEVERS
+            .peek()
+            .get(&title)
+            .copied()
+            .filter(|_| hold_state)
+    });
+    // Travel towards the far end, negative towards the near one
+    let mut stick_pos = use_signal(|| saved.map_or(0.0, |x| x.pos));
     let mut drag_start = use_signal(|| None::<f64>);
-    let mut active_dir = use_signal(|| Direction::Neutral);
+    let mut active_dir = use_signal(|| saved.map_or(Direction::Neutral, |x| x.dir));
+    let mut output = use_signal(|| saved.map_or(0.0, |x| x.output));
+    let mut last_tap = use_signal(|| None::<Instant>);
+
+    let tuning = use_tuning(title.clone(), tuning);
+    let ends = (
+        Direction::from_angle(angle),
+        Direction::from_angle(angle + 180.0),
+    );
+
+    // Distance along the axis towards the far end, in screen pixels
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let project = move |x: f64, y: f64| x * sin - y * cos;
+
+    // What a held lever sends for `dir` pushed `output` of the way
+    let value_cmd = move |dir: Direction, output: f64| {
+        press_cmd(dir)
+            .map(|x| Command::new(x.action, Status::Value((output * 100.0).round() as i32)))
+    };
 
-    let max_radius = 60.0;
-    let deadzone = 15.0;
+    use_effect({
+        let title = title.clone();
+        move || {
+            let state = LeverState {
+                pos: stick_pos(),
+                output: output(),
+                dir: active_dir(),
+            };
+            if hold_state {
+                HELD_LEVERS.write().insert(title.clone(), state);
+            }
+        }
+    });
 
-    // Logic implementation (same as before, just styling changed)
-    let mut handle_move = move |client_y: f64| {
-        if let Some(start) = drag_start() {
-            let delta_y = client_y - start;
-            let vis_y = if delta_y.abs() > max_radius {
-                let ratio = max_radius / delta_y.abs();
-                delta_y * ratio
+    use_future(move || async move {
+        if !hold_state {
+            return;
+        }
+        loop {
+            time::sleep(HOLD_REPEAT).await;
+            if let Some(cmd) = value_cmd(*active_dir.peek(), *output.peek()) {
+                send_command(cmd).await;
+            }
+        }
+    });
+
+    let mut center = move || {
+        let current = *active_dir.peek();
+        stick_pos.set(0.0);
+        output.set(0.0);
+        active_dir.set(Direction::Neutral);
+        if let Some(cmd) = value_cmd(current, 0.0) {
+            spawn(send_command(cmd));
+        }
+    };
+
+    let mut handle_start = move |x: f64, y: f64| {
+        if hold {
+            let now = Instant::now();
+            if last_tap().is_some_and(|x| now - x < DOUBLE_TAP) {
+                center();
+                last_tap.set(None);
             } else {
-                delta_y
-            };
+                last_tap.set(Some(now));
+            }
+        }
+        // A held lever picks up from where it was left instead of jumping back to the center
+        drag_start.set(Some(project(x, y) - stick_pos() / tuning().sensitivity));
+    };
 
-            stick_pos.set(vis_y);
+    let mut handle_move = move |x: f64, y: f64| {
+        if let Some(start) = drag_start() {
+            let tuning = tuning();
+            let delta = project(x, y) - start;
+            let travel = tuning.travel(delta.abs());
+            let new_output = tuning.output(delta.abs());
 
-            let new_dir = if delta_y.abs() < deadzone {
+            let new_dir = if travel < tuning.deadzone {
                 Direction::Neutral
-            } else if delta_y > 0.0 {
-                Direction::Down
+            } else if delta > 0.0 {
+                ends.0
             } else {
-                Direction::Up
+                ends.1
             };
 
             let current = *active_dir.read();
-            if new_dir != current {
+            let changed = (output() * 100.0).round() != (new_output * 100.0).round();
+            feel_stick(
+                current,
+                new_dir,
+                stick_pos().abs(),
+                travel,
+                tuning.max_radius,
+            );
+            stick_pos.set(travel.copysign(delta));
+            output.set(new_output);
+            active_dir.set(new_dir);
+
+            if hold {
+                spawn(async move {
+                    if new_dir != current
+                        && let Some(cmd) = value_cmd(current, 0.0)
+                    {
+                        send_command(cmd).await;
+                    }
+                    if (changed || new_dir != current)
+                        && let Some(cmd) = value_cmd(new_dir, new_output)
+                    {
+                        send_command(cmd).await;
+                    }
+                });
+            } else if new_dir != current {
                 spawn(async move {
                     if let Some(cmd) = release_cmd(current) {
                         send_command(cmd).await;
@@ -314,14 +549,17 @@ fn VerticalJoystick(
                         send_command(cmd).await;
                     }
                 });
-                active_dir.set(new_dir);
             }
         }
     };
 
     let mut handle_end = move || {
         drag_start.set(None);
+        if hold {
+            return;
+        }
         stick_pos.set(0.0);
+        output.set(0.0);
         let current = *active_dir.read();
         spawn(async move {
             if let Some(cmd) = release_cmd(current) {
@@ -331,52 +569,73 @@ fn VerticalJoystick(
         active_dir.set(Direction::Neutral);
     };
 
+    // The track is drawn upright and rotated, so reserve room for its rotated bounds
+    let (width, height) = (96.0, 240.0);
+    let bounds_w = height * sin.abs() + width * cos.abs();
+    let bounds_h = height * cos.abs() + width * sin.abs();
+
     rsx! {
         div { class: "flex flex-col items-center gap-6",
             span { class: "text-white/60 font-bold uppercase tracking-[0.2em] text-sm drop-shadow-md", "{title}" }
 
-            // 1. The Gradient Stroke Container
-            div { class: "glass-border-gradient",
-                // 2. The Inner Glass Track
-                div {
-                    class: "relative w-24 h-[240px] rounded-full glass-panel flex justify-center items-center overflow-hidden",
+            div {
+                class: "flex items-center justify-center",
+                width: "{bounds_w}px",
+                height: "{bounds_h}px",
 
-                    // Touch Handlers
-                    ontouchstart: move |e| {
-                        if let Some(t) = e.data.touches().first() {
-                            drag_start.set(Some(t.client_coordinates().y));
-                        }
-                    },
-                    ontouchmove: move |e| {
-                        if let Some(t) = e.data.touches().first() {
-                            handle_move(t.client_coordinates().y);
+                // 1. The Gradient Stroke Container
+                div { class: "glass-border-gradient shrink-0", transform: "rotate({angle}deg)",
+                    // 2. The Inner Glass Track
+                    div {
+                        class: "relative rounded-full glass-panel flex justify-center items-center overflow-hidden",
+                        width: "{width}px",
+                        height: "{height}px",
+
+                        // Touch Handlers
+                        ontouchstart: move |e| {
+                            if let Some(t) = e.data.touches().first() {
+                                let cords = t.client_coordinates();
+                                handle_start(cords.x, cords.y);
+                            }
+                        },
+                        ontouchmove: move |e| {
+                            if let Some(t) = e.data.touches().first() {
+                                let cords = t.client_coordinates();
+                                handle_move(cords.x, cords.y);
+                            }
+                        },
+                        ontouchend: move |_| handle_end(),
+
+                        // Placed as if upright; the arrows turn with the track
+                        for (dir, end) in [(Direction::Up, ends.0), (Direction::Down, ends.1)] {
+                            if let Some(cmd) = press_cmd(end) {
+                                DirectionButton {
+                                    dir,
+                                    command: Command::new(cmd.action, Status::BlinkOnce),
+                                    gap: 3,
+                                    padding: 5,
+                                }
+                            }
                         }
-                    },
-                    ontouchend: move |_| handle_end(),
 
-                    for dir in [Direction::Up, Direction::Down] {
-                        DirectionButton {
-                            dir,
-                            send_command_args: (press_cmd(dir).context("well this has gone horribly wrong(state)")?.0, "blink_once"),
-                            gap: 3,
-                            padding: 5,
-                        }
-                    }
 
+                        // Visual Track Line
+                        div { class: "absolute w-[2px] h-3/4 bg-white/5 rounded-full" }
 
-                    // Visual Track Line
-                    div { class: "absolute w-[2px] h-3/4 bg-white/5 rounded-full" }
+                        // 3. The Interactive Puck
+                        div {
+                            class: "absolute w-16 h-16 rounded-full glass-puck flex items-center justify-center duration-800 ease-(--quick-easing)",
+                            will_change: "transform",
+                            transition_property: if drag_start().is_none() { "transform" } else { "none" },
+                            transform: "translateY({-stick_pos()}px)",
 
-                    // 3. The Interactive Puck
-                    div {
-                        class: "absolute w-16 h-16 rounded-full glass-puck flex items-center justify-center duration-800 ease-(--quick-easing)",
-                        will_change: "transform",
-                        transition_property: if drag_start().is_none() { "transform" } else { "none" },
-                        transform: "translateY({stick_pos}px)",
+                            // Output glow, brighter the harder the stick is pushed
+                            div { class: "absolute inset-0 rounded-full bg-pink-500/40", opacity: "{output}" }
 
-                        // Icon
-                        div { class: "text-white/90 text-4xl font-bold drop-shadow-lg",
-                            "●"
+                            // Icon
+                            div { class: "text-white/90 text-4xl font-bold drop-shadow-lg",
+                                "●"
+                            }
                         }
                     }
                 }
@@ -387,22 +646,18 @@ fn VerticalJoystick(
 
 // --- COMPONENT: 360 Analog Joystick ---
 #[component]
-fn AnalogJoystick() -> Element {
+fn AnalogJoystick(
+    title: String,
+    press_cmd: Callback<Direction, Option<Command>>,
+    release_cmd: Callback<Direction, Option<Command>>,
+    #[props(default = StickTuning::new(50.0, 10.0))] tuning: StickTuning,
+) -> Element {
     let mut stick_pos = use_signal(|| (0.0f64, 0.0f64));
     let mut drag_start = use_signal(|| None::<(f64, f64)>);
     let mut active_dir = use_signal(|| Direction::Neutral);
+    let mut output = use_signal(|| 0.0f64);
 
-    let max_radius = 50.0; // Visual restriction
-    let deadzone = 10.0;
-
-    let press_cmd = move |cmd| match cmd {
-        Direction::Neutral => None,
-        x => Some((x.to_command(), "pressed")),
-    };
-    let release_cmd = move |cmd| match cmd {
-        Direction::Neutral => None,
-        x => Some((x.to_command(), "released")),
-    };
+    let tuning = use_tuning(title.clone(), tuning);
 
     let mut handle_move = move |client_x: f64, client_y: f64| {
         if let Some(start) = drag_start() {
@@ -410,16 +665,20 @@ fn AnalogJoystick() -> Element {
             let delta_y = client_y - start.1;
             let distance = delta_x.hypot(delta_y);
 
-            let (vis_x, vis_y) = if distance > max_radius {
-                let ratio = max_radius / distance;
-                (delta_x * ratio, delta_y * ratio)
+            let tuning = tuning();
+            let travel = tuning.travel(distance);
+            let ratio = if distance > 0.0 {
+                travel / distance
             } else {
-                (delta_x, delta_y)
+                0.0
             };
 
-            stick_pos.set((vis_x, vis_y));
+            let (x, y) = stick_pos();
+            let previous = x.hypot(y);
+            stick_pos.set((delta_x * ratio, delta_y * ratio));
+            output.set(tuning.output(distance));
 
-            let new_dir = if distance < deadzone {
+            let new_dir = if travel < tuning.deadzone {
                 Direction::Neutral
             } else if delta_x.abs() > delta_y.abs() {
                 if delta_x > 0.0 {
@@ -434,6 +693,7 @@ fn AnalogJoystick() -> Element {
             };
 
             let current = *active_dir.read();
+            feel_stick(current, new_dir, previous, travel, tuning.max_radius);
             if new_dir != current {
                 spawn(async move {
                     if let Some(cmd) = release_cmd(current) {
@@ -451,6 +711,7 @@ fn AnalogJoystick() -> Element {
     let mut handle_end = move || {
         drag_start.set(None);
         stick_pos.set((0.0, 0.0));
+        output.set(0.0);
         let current = *active_dir.read();
         spawn(async move {
             if let Some(cmd) = release_cmd(current) {
@@ -461,46 +722,54 @@ fn AnalogJoystick() -> Element {
     };
 
     rsx! {
-        // 1. Gradient Stroke Container
-        div { class: "glass-border-gradient",
-            // 2. Inner Glass Base
-            div {
-                class: "relative size-[250px] rounded-full glass-panel flex items-center justify-center",
-
-                ontouchstart: move |e| {
-                    if let Some(t) = e.data.touches().first() {
-                        let cords = t.client_coordinates();
-                        drag_start.set(Some((cords.x, cords.y)));
-                    }
-                },
-                ontouchmove: move |e| {
-                    if let Some(t) = e.touches().first() {
-                        let cords = t.client_coordinates();
-                        handle_move(cords.x, cords.y);
-                    }
-                },
-                ontouchend: move |_| handle_end(),
-
-                for dir in [Direction::Up, Direction::Left, Direction::Down, Direction::Right] {
-                    DirectionButton {
-                        dir,
-                        send_command_args: (dir.to_command(), "blink_once"),
-                        gap: 0,
-                        padding: 8,
-                    }
-                }
+        div { class: "flex flex-col items-center gap-6",
+            span { class: "text-white/60 font-bold uppercase tracking-[0.2em] text-sm drop-shadow-md", "{title}" }
 
-                // 3. The Interactive Puck
+            // 1. Gradient Stroke Container
+            div { class: "glass-border-gradient",
+                // 2. Inner Glass Base
                 div {
-                    class: "absolute w-24 h-24 rounded-full glass-puck flex items-center justify-center duration-800 ease-(--quick-easing)",
-                    will_change: "transform",
-                    transition_property: if drag_start().is_none() { "transform" } else { "none" },
-                    transform: "translate({stick_pos().0}px, {stick_pos().1}px)",
+                    class: "relative size-[250px] rounded-full glass-panel flex items-center justify-center",
 
-                    div { class: "text-white/90 text-4xl font-bold drop-shadow-lg",
-                        "●"
+                    ontouchstart: move |e| {
+                        if let Some(t) = e.data.touches().first() {
+                            let cords = t.client_coordinates();
+                            drag_start.set(Some((cords.x, cords.y)));
+                        }
+                    },
+                    ontouchmove: move |e| {
+                        if let Some(t) = e.touches().first() {
+                            let cords = t.client_coordinates();
+                            handle_move(cords.x, cords.y);
+                        }
+                    },
+                    ontouchend: move |_| handle_end(),
+
+                    for dir in [Direction::Up, Direction::Left, Direction::Down, Direction::Right] {
+                        if let Some(cmd) = press_cmd(dir) {
+                            DirectionButton {
+                                dir,
+                                command: Command::new(cmd.action, Status::BlinkOnce),
+                                gap: 0,
+                                padding: 8,
+                            }
+                        }
                     }
 
+                    // 3. The Interactive Puck
+                    div {
+                        class: "absolute w-24 h-24 rounded-full glass-puck flex items-center justify-center duration-800 ease-(--quick-easing)",
+                        will_change: "transform",
+                        transition_property: if drag_start().is_none() { "transform" } else { "none" },
+                        transform: "translate({stick_pos().0}px, {stick_pos().1}px)",
+
+                        div { class: "absolute inset-0 rounded-full bg-pink-500/40", opacity: "{output}" }
+
+                        div { class: "text-white/90 text-4xl font-bold drop-shadow-lg",
+                            "●"
+                        }
+
+                    }
                 }
             }
         }
@@ -541,7 +810,7 @@ impl IntoAttributeValue for TailwindNumber {
 #[component]
 fn DirectionButton(
     dir: Direction,
-    send_command_args: (&'static str, &'static str),
+    command: Command,
     #[props(into)] gap: TailwindNumber,
     #[props(into)] padding: TailwindNumber,
 ) -> Element {
@@ -558,9 +827,7 @@ fn DirectionButton(
             class: "absolute size-[20px] flex items-center justify-center group",
             style,
             padding,
-            ontouchstart: move |e| async move {
-                send_command(send_command_args).await;
-            },
+            ontouchstart: move |_| send_command(command.clone()),
             div { class: "text-white/30 group-active:text-white/90 text-3xl font-bold drop-shadow-lg",
                 {dir.icon()?}
             }
@@ -568,20 +835,99 @@ fn DirectionButton(
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ButtonMode {
+    /// `pressed` while held, `released` when let go, like a horn.
+    #[default]
+    Momentary,
+    /// Latches `on` and `off` on alternate taps, like headlights.
+    Toggle,
+    /// A single `blink_once` per tap, like the direction buttons.
+    OneShot,
+}
+
+impl ButtonMode {
+    const ALL: [Self; 3] = [Self::Momentary, Self::Toggle, Self::OneShot];
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Momentary => "Momentary",
+            Self::Toggle => "Toggle",
+            Self::OneShot => "One-shot",
+        }
+    }
+}
+
+// --- COMPONENT: General purpose button ---
+#[component]
+fn ActionButton(title: String, action: ReadSignal<String>, mode: ButtonMode) -> Element {
+    // Held for momentary buttons, latched on for toggles; one-shots only light up while touched.
+    let mut active = use_signal(|| false);
+    let supported = capabilities::supports(&action.read());
+
+    let mut press = move || match mode {
+        ButtonMode::Momentary if !active() => {
+            active.set(true);
+            spawn(send_command(Command::pressed(action())));
+        }
+        ButtonMode::Toggle => {
+            let on = !active();
+            active.set(on);
+            let status = if on { Status::On } else { Status::Off };
+            spawn(send_command(Command::new(action(), status)));
+        }
+        ButtonMode::OneShot => {
+            active.set(true);
+            spawn(send_command(Command::new(action(), Status::BlinkOnce)));
+        }
+        ButtonMode::Momentary => {}
+    };
+    let mut release = move || match mode {
+        ButtonMode::Momentary if active() => {
+            active.set(false);
+            spawn(send_command(Command::released(action())));
+        }
+        ButtonMode::OneShot => active.set(false),
+        _ => {}
+    };
+
+    rsx! {
+        div { class: "glass-border-gradient",
+            button {
+                class: format!("size-24 rounded-full glass-panel flex flex-col items-center justify-center gap-1 text-sm font-bold uppercase tracking-wider transition-all {} {}",
+                    if active() { "bg-white/30 text-white shadow-inner" } else { "text-white/60" },
+                    if supported { "" } else { "opacity-30" }),
+                disabled: !supported,
+                title: if !supported { "Not supported by the robot's firmware" },
+                onpointerdown: move |_| press(),
+                onpointerup: move |_| release(),
+                onpointerleave: move |_| release(),
+                "{title}"
+                if mode == ButtonMode::Toggle {
+                    span { class: "text-[10px] text-white/40", if active() { "on" } else { "off" } }
+                }
+            }
+        }
+    }
+}
+
+/// Web builds are served by the robot they drive, so there's nothing to pick.
+#[cfg(not(feature = "discovery"))]
+#[component]
+fn RobotPicker() -> Element {
+    VNode::empty()
+}
+
 #[component]
 fn BlinkSlider() -> Element {
     rsx! {
         CustomSlider {
-            left: "fast (50ms)",
-            right: "slow (150ms)",
-            step: 5,
             title: "Blink Rate",
-            unit: "ms",
             details: "Adjust the strobe frequency for signals",
-            default_value: 75,
-            command_type: "blink_rate",
-            min: 50,
-            max: 150
+            setting: capabilities::limits(BLINK_RATE),
+            low: "fast",
+            high: "slow",
         }
     }
 }
@@ -590,88 +936,341 @@ fn BlinkSlider() -> Element {
 fn PwmSlider() -> Element {
     rsx! {
         CustomSlider {
-            left: "none(0%)",
-            right: "full(100%)",
             title: "PWM Percentage",
-            step: 5,
-            unit: "%",
             details: "Adjust the PWM duty cycle using percentages",
-            default_value: 100,
-            command_type: "pwm_percentage",
-            min: 20,
-            max: 100
+            setting: capabilities::limits(PWM_PERCENTAGE),
+            low: "weakest",
+            high: "full",
         }
     }
 }
 
 #[component]
 fn FrequencySlider() -> Element {
-    let max = 48000;
-    let min = 500;
     rsx! {
         CustomSlider {
-            left: "low({min})",
-            right: "high({max})",
-            step: 500,
             title: "PWM Frequency",
-            unit: "Hz",
             details: "A higher frequency produces less hum, but lesser strength",
-            default_value: 8000,
-            command_type: "frequency_kilohertz",
-            min,
-            max
+            setting: capabilities::limits(PWM_FREQUENCY),
+            low: "low",
+            high: "high",
         }
     }
 }
 
+/// How long the unlock button is held before a value outside the safe range is sent.
+const UNLOCK_HOLD: Duration = Duration::from_millis(1500);
+
+/// Delay before a held nudge button starts repeating.
+const NUDGE_DELAY: Duration = Duration::from_millis(400);
+const NUDGE_REPEAT: Duration = Duration::from_millis(80);
+
+/// How much a held nudge button adds per repeat: single units at first, ten times more every
+/// eight repeats, up to a tenth of the range.
+fn nudge_size(repeats: u32, range: i32) -> i32 {
+    10i32.saturating_pow(repeats / 8).min(range / 10).max(1)
+}
+
+/// Sends `setting`'s value as it's dragged, typed or nudged, refusing values outside its limits.
 #[component]
 fn CustomSlider(
-    default_value: i32,
-    unit: String,
     title: String,
     details: String,
-    command_type: ReadSignal<String>,
-    left: String,
-    right: String,
-    step: i32,
-    min: i32,
-    max: i32,
+    setting: ReadSignal<SettingLimits>,
+    /// Words for the ends of the range, shown next to `min` and `max`.
+    #[props(default)]
+    low: String,
+    #[props(default)] high: String,
 ) -> Element {
-    let mut blink_len = use_signal(|| default_value);
+    let value = move || settings::value(&setting.read());
+    let refuse = move |reason: String| settings::refuse(&setting.read().action, reason);
+    let mut editing = use_signal(|| false);
+    let mut draft = use_signal(String::new);
+    let mut nudging = use_signal(|| None::<Task>);
+    let mut shown = use_signal(|| setting.peek().unit);
+    let mut unlocking = use_signal(|| None::<Task>);
+
+    // New limits, e.g. once the robot reports its own, may measure in another unit
+    use_effect(move || shown.set(setting.read().unit));
+
+    // Values outside the safe range wait in `settings::pending` until unlocked
+    let commit = move |val: i32| settings::set(&setting.read(), val);
+    let mut start_unlocking = move || {
+        let task = spawn(async move {
+            time::sleep(UNLOCK_HOLD).await;
+            unlocking.set(None);
+            settings::unlock(&setting.read());
+        });
+        if let Some(previous) = unlocking.replace(Some(task)) {
+            previous.cancel();
+        }
+    };
+    let mut stop_unlocking = move || {
+        if let Some(task) = unlocking.take() {
+            task.cancel();
+        }
+    };
+    // Enter and blur both end an edit; whichever comes first commits it
+    let mut finish_editing = move |keep: bool| {
+        if !editing() {
+            return;
+        }
+        editing.set(false);
+        if !keep {
+            return;
+        }
+        // Typed in the unit shown, kept in the setting's own
+        let raw = draft();
+        let unit = setting.read().unit;
+        match raw.trim().parse::<f64>() {
+            Ok(x) if x.is_finite() => match shown().convert(x, unit) {
+                Some(x) => commit(x.round() as i32),
+                None => refuse(format!("`{raw}` isn't in {unit}")),
+            },
+            _ => refuse(format!("`{raw}` isn't a number")),
+        }
+    };
+    let nudge = move |sign: i32, repeats: u32| {
+        let SettingLimits { min, max, .. } = *setting.read();
+        // Not `clamp`, which panics should a layout's `min` ever be above its `max`
+        let step = sign * nudge_size(repeats, max.saturating_sub(min));
+        let next = value().saturating_add(step).min(max).max(min);
+        if next != value() {
+            commit(next);
+        }
+    };
+    let mut start_nudging = move |sign: i32| {
+        nudge(sign, 0);
+        let task = spawn(async move {
+            time::sleep(NUDGE_DELAY).await;
+            for repeats in 1.. {
+                nudge(sign, repeats);
+                time::sleep(NUDGE_REPEAT).await;
+            }
+        });
+        if let Some(previous) = nudging.replace(Some(task)) {
+            previous.cancel();
+        }
+    };
+    let mut stop_nudging = move || {
+        if let Some(task) = nudging.take() {
+            task.cancel();
+        }
+    };
+
+    let limits = setting();
+    let (slider_min, slider_max, slider_step) = limits.slider_range();
+    let SettingLimits {
+        action,
+        min,
+        max,
+        unit,
+        default,
+        ..
+    } = limits.clone();
+    let saved = settings::saved(&limits);
+    let label = |word: &str, bound: i32| {
+        let bound = unit.format(bound, shown());
+        if word.is_empty() {
+            bound
+        } else {
+            format!("{word} ({bound})")
+        }
+    };
+    let alternatives: Vec<Unit> = unit.alternatives().collect();
+    let (left, right) = (label(&low, min), label(&high, max));
+    let safe_range = limits.safe.map(|x| {
+        format!(
+            "safe {}–{}",
+            unit.format(x.min, shown()),
+            unit.format(x.max, shown())
+        )
+    });
+    let supported = capabilities::supports(&action);
+    let unsafe_value = !limits.is_safe(value());
+    // Spelled out in full so Tailwind finds the classes
+    let (text, accent) = if unsafe_value {
+        ("text-amber-500", "accent-amber-500 hover:accent-amber-400")
+    } else {
+        ("text-pink-500", "accent-pink-500 hover:accent-pink-400")
+    };
 
     rsx! {
         div { class: "glass-border-gradient !rounded-3xl w-full",
-            div { class: "glass-panel p-8 rounded-3xl flex flex-col gap-6",
-                div { class: "flex justify-between items-end",
+            div {
+                class: format!(
+                    "glass-panel p-8 rounded-3xl flex flex-col gap-6 {}",
+                    if unsafe_value { "ring-2 ring-amber-500/60" } else { "" },
+                ),
+                div { class: "flex justify-between items-end gap-4",
                     div {
                         h3 { class: "text-xl font-bold text-white", "{title}" }
                         p { class: "text-sm text-white/40", "{details}" }
                     }
-                    span { class: "text-4xl font-mono font-bold text-pink-500", "{blink_len}{unit}" }
+                    div { class: "flex items-center gap-2",
+                        NudgeButton {
+                            icon: "−",
+                            disabled: !supported,
+                            start: move |_| start_nudging(-1),
+                            stop: move |_| stop_nudging(),
+                        }
+                        if editing() {
+                            input {
+                                class: "w-40 bg-transparent border-b border-pink-500 outline-none text-4xl font-mono font-bold text-pink-500 text-right select-text",
+                                r#type: "number",
+                                inputmode: "decimal",
+                                step: "any",
+                                value: "{draft}",
+                                onmounted: move |evt| async move {
+                                    let _ = evt.set_focus(true).await;
+                                },
+                                oninput: move |evt| draft.set(evt.value()),
+                                onkeydown: move |evt| match evt.key() {
+                                    Key::Enter => finish_editing(true),
+                                    Key::Escape => finish_editing(false),
+                                    _ => {}
+                                },
+                                onblur: move |_| finish_editing(true),
+                            }
+                        } else {
+                            button {
+                                class: "text-4xl font-mono font-bold {text} disabled:opacity-50",
+                                title: "Tap to type a value",
+                                disabled: !supported,
+                                onclick: move |_| {
+                                    let shown = unit.convert(f64::from(value()), shown());
+                                    draft.set(shown.map(|x| Decimal::new(x).to_string()).unwrap_or_default());
+                                    editing.set(true);
+                                },
+                                "{unit.format(value(), shown())}"
+                            }
+                        }
+                        if alternatives.len() > 1 {
+                            button {
+                                class: "px-2 py-1 rounded-lg text-xs font-bold text-white/40 hover:bg-white/10 transition-all",
+                                title: "Switch the unit shown",
+                                onclick: move |_| {
+                                    let next = alternatives
+                                        .iter()
+                                        .cycle()
+                                        .skip_while(|x| **x != shown())
+                                        .nth(1)
+                                        .copied();
+                                    if let Some(next) = next {
+                                        shown.set(next);
+                                    }
+                                },
+                                "{shown}"
+                            }
+                        }
+                        NudgeButton {
+                            icon: "+",
+                            disabled: !supported,
+                            start: move |_| start_nudging(1),
+                            stop: move |_| stop_nudging(),
+                        }
+                    }
                 }
 
                 input {
                     r#type: "range",
-                    min,
-                    max,
-                    step,
-                    value: "{blink_len}",
-                    class: "w-full h-2 bg-slate-700/50 rounded-lg appearance-none cursor-pointer accent-pink-500 hover:accent-pink-400 transition-all",
+                    min: slider_min,
+                    max: slider_max,
+                    step: slider_step,
+                    value: "{limits.position(value())}",
+                    disabled: !supported,
+                    class: "w-full h-2 bg-slate-700/50 rounded-lg appearance-none cursor-pointer {accent} transition-all",
                     oninput: move |evt| {
-                        if let Ok(val) = evt.value().parse::<i32>() {
-                            blink_len.set(val);
-                            spawn(async move {
-                                send_command((&command_type(), &val.to_string())).await;
-                            });
+                        let raw = evt.value();
+                        match raw.parse::<i32>() {
+                            Ok(position) => commit(setting.read().value_at(position)),
+                            Err(_) => refuse(format!("`{raw}` isn't a whole number")),
                         }
                     },
                 }
 
                 div { class: "flex justify-between text-[10px] font-bold text-white/20 uppercase tracking-tighter",
                     span { {left} }
+                    if let Some(safe_range) = safe_range {
+                        span { class: "text-amber-500/60", {safe_range} }
+                    }
                     span { {right} }
                 }
+
+                if let Some(val) = settings::pending(&action) {
+                    div { class: "flex items-center gap-4 p-4 rounded-2xl bg-amber-500/10 border border-amber-500/40",
+                        p { class: "flex-1 text-sm text-amber-300",
+                            "⚠ {unit.format(val, shown())} is outside the safe range and can damage the hardware."
+                        }
+                        button {
+                            class: "relative overflow-hidden px-4 py-2 rounded-full text-sm font-bold text-amber-300 glass-panel",
+                            title: "Hold to send anyway",
+                            onpointerdown: move |_| start_unlocking(),
+                            onpointerup: move |_| stop_unlocking(),
+                            onpointerleave: move |_| stop_unlocking(),
+                            onpointercancel: move |_| stop_unlocking(),
+                            // Fills up while held
+                            span {
+                                class: "absolute inset-y-0 left-0 bg-amber-500/40 transition-[width] ease-linear",
+                                style: if unlocking().is_some() { "width: 100%; transition-duration: {UNLOCK_HOLD.as_millis()}ms" } else { "width: 0" },
+                            }
+                            span { class: "relative", "Hold to unlock" }
+                        }
+                        button {
+                            class: "px-3 py-2 rounded-full text-sm font-bold text-white/40 hover:bg-white/10 transition-all",
+                            onclick: {
+                                let action = action.clone();
+                                move |_| settings::cancel(&action)
+                            },
+                            "Cancel"
+                        }
+                    }
+                }
+
+                div { class: "flex items-center gap-4",
+                    if !supported {
+                        p { class: "text-sm text-white/40", "Not supported by the robot's firmware" }
+                    } else if let Some(error) = settings::error(&action) {
+                        p { class: "text-sm text-red-400", "{error}" }
+                    }
+                    div { class: "flex gap-1 ml-auto",
+                        button {
+                            class: "px-3 py-1 rounded-full text-xs font-bold text-white/40 hover:bg-white/10 transition-all disabled:opacity-30",
+                            disabled: !supported || value() == saved,
+                            title: "Send the value saved for this robot",
+                            onclick: move |_| commit(saved),
+                            "Revert"
+                        }
+                        button {
+                            class: "px-3 py-1 rounded-full text-xs font-bold text-white/40 hover:bg-white/10 transition-all disabled:opacity-30",
+                            disabled: !supported || value() == default,
+                            onclick: move |_| commit(default),
+                            "Default"
+                        }
+                    }
+                }
             }
         }
     }
 }
+
+/// Nudges a setting while held; `start` fires on press and `stop` on release.
+#[component]
+fn NudgeButton(
+    icon: &'static str,
+    disabled: bool,
+    start: EventHandler<()>,
+    stop: EventHandler<()>,
+) -> Element {
+    rsx! {
+        button {
+            class: "size-10 rounded-full glass-panel text-xl font-bold text-white/60 hover:bg-white/20 active:bg-white/30 transition-all disabled:opacity-30",
+            disabled,
+            onpointerdown: move |_| start(()),
+            onpointerup: move |_| stop(()),
+            onpointerleave: move |_| stop(()),
+            onpointercancel: move |_| stop(()),
+            "{icon}"
+        }
+    }
+}
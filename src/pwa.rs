@@ -0,0 +1,71 @@
+//! Installing the web build as an app: the manifest, the offline-first service worker in
+//! `public/sw.js`, and the browser's install-to-home-screen prompt.
+
+use dioxus::prelude::*;
+
+/// Whether the browser offered to install the app and it hasn't been installed yet.
+static INSTALLABLE: GlobalSignal<bool> = Signal::global(|| false);
+
+const SCRIPT: &str = r"
+    navigator.serviceWorker?.register('/sw.js').catch((err) => {
+        console.warn('Service worker not registered', err);
+    });
+    window.addEventListener('beforeinstallprompt', (event) => {
+        // Keep the prompt for the install button instead of the browser's own banner
+        event.preventDefault();
+        window.installPrompt = event;
+        dioxus.send(true);
+    });
+    window.addEventListener('appinstalled', () => {
+        window.installPrompt = null;
+        dioxus.send(false);
+    });
+    await new Promise(() => {});
+";
+
+/// Registers the service worker and watches for the install prompt.
+pub fn use_pwa() {
+    use_hook(|| {
+        let mut eval = document::eval(SCRIPT);
+        spawn(async move {
+            while let Ok(installable) = eval.recv().await {
+                *INSTALLABLE.write() = installable;
+            }
+        });
+    });
+}
+
+async fn install() {
+    let eval = document::eval(
+        r"
+            const prompt = window.installPrompt;
+            if (!prompt) return false;
+            prompt.prompt();
+            const { outcome } = await prompt.userChoice;
+            window.installPrompt = null;
+            return outcome === 'accepted';
+        ",
+    );
+    match eval.join::<bool>().await {
+        Ok(accepted) => info!("Install prompt answered, accepted: {accepted}"),
+        Err(err) => warn!("Install prompt failed: {err}"),
+    }
+    // A prompt can only be shown once
+    *INSTALLABLE.write() = false;
+}
+
+// --- COMPONENT: Install button for the header ---
+#[component]
+pub fn InstallButton() -> Element {
+    if !INSTALLABLE() {
+        return VNode::empty();
+    }
+
+    rsx! {
+        button {
+            class: "px-4 py-3 rounded-xl glass-panel text-sm font-bold text-white/80 hover:bg-white/20 transition-all",
+            onclick: move |_| install(),
+            "⇩ Install"
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Recording the commands the controls send and replaying them later as named macros.
+//!
+//! Playback follows the recorded timing against a fixed start instant, so slow requests delay
+//! a single step rather than every step after it. Any touch aborts playback, and whatever the
+//! macro left pressed, deflected or switched on is undone when it finishes or is aborted.
+
+use std::time::Duration;
+
+use dioxus::{
+    core::{Task, spawn_forever},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command::{Command, Status, send_command},
+    storage,
+    time::{Instant, sleep},
+};
+
+const STORAGE_KEY: &str = "macros";
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Step {
+    /// Time since the macro's first step.
+    pub offset_ms: u64,
+    pub command: Command,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Macro {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+impl Macro {
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.steps.last().map_or(0, |x| x.offset_ms))
+    }
+}
+
+struct Recording {
+    started: Instant,
+    steps: Vec<Step>,
+}
+
+struct Playback {
+    name: String,
+    task: Task,
+}
+
+pub static MACROS: GlobalSignal<Vec<Macro>> = Signal::global(Vec::new);
+static RECORDING: GlobalSignal<Option<Recording>> = Signal::global(|| None);
+static PLAYBACK: GlobalSignal<Option<Playback>> = Signal::global(|| None);
+/// Presses and toggles sent by the running macro that haven't been undone yet.
+static HELD: GlobalSignal<Vec<Command>> = Signal::global(Vec::new);
+
+/// Appends `command` to the current recording, unless it's a macro being played back.
+pub fn capture(command: &Command) {
+    if RECORDING.peek().is_none() || PLAYBACK.peek().is_some() {
+        return;
+    }
+    if let Some(recording) = RECORDING.write().as_mut() {
+        if is_repeat(&recording.steps, command) {
+            return;
+        }
+        let offset_ms = recording.started.elapsed().as_millis() as u64;
+        recording.steps.push(Step {
+            offset_ms,
+            command: command.clone(),
+        });
+    }
+}
+
+/// Whether `command` only repeats the value its action was last sent, like a held lever does.
+/// Taps, like blinking twice, are never repeats.
+fn is_repeat(steps: &[Step], command: &Command) -> bool {
+    matches!(command.status, Status::Value(_))
+        && steps
+            .iter()
+            .rev()
+            .find(|x| x.command.action == command.action)
+            .is_some_and(|x| x.command == *command)
+}
+
+fn start_recording() {
+    *RECORDING.write() = Some(Recording {
+        started: Instant::now(),
+        steps: Vec::new(),
+    });
+}
+
+fn stop_recording() {
+    let Some(recording) = RECORDING.write().take() else {
+        return;
+    };
+    let Some(first) = recording.steps.first().map(|x| x.offset_ms) else {
+        return;
+    };
+
+    // Playback should start right away, not after however long it took to reach the controls.
+    let steps = recording
+        .steps
+        .into_iter()
+        .map(|x| Step {
+            offset_ms: x.offset_ms - first,
+            ..x
+        })
+        .collect();
+    let name = format!("Macro {}", MACROS.peek().len() + 1);
+    add(Macro { name, steps });
+}
+
+/// Keeps `macro_` alongside the recorded ones.
+pub fn add(macro_: Macro) {
+    MACROS.write().push(macro_);
+    save_macros();
+}
+
+fn save_macros() {
+    storage::save(STORAGE_KEY, &*MACROS.peek());
+}
+
+fn track(command: &Command) {
+    let mut held = HELD.write();
+    match command.status {
+        Status::Released | Status::Off | Status::Value(0) => {
+            held.retain(|x| x.action != command.action);
+        }
+        // A lever moved while held replaces its last deflection
+        _ if command.release().is_some() => {
+            held.retain(|x| x.action != command.action);
+            held.push(command.clone());
+        }
+        _ => {}
+    }
+}
+
+/// Takes what the running macro is holding, to be released.
+fn take_held() -> Vec<Command> {
+    std::mem::take(&mut *HELD.write())
+}
+
+async fn release(held: Vec<Command>) {
+    for command in held.iter().filter_map(Command::release) {
+        send_command(command).await;
+    }
+}
+
+pub fn play(macro_: Macro) {
+    abort();
+
+    let name = macro_.name.clone();
+    // Spawned at the root so closing whichever panel started it doesn't cancel it halfway.
+    let task = spawn_forever(async move {
+        let started = Instant::now();
+        for step in macro_.steps {
+            let due = Duration::from_millis(step.offset_ms);
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                sleep(wait).await;
+            }
+            track(&step.command);
+            send_command(step.command).await;
+        }
+        // Finished first, so a touch meanwhile can't abort the release halfway
+        *PLAYBACK.write() = None;
+        release(take_held()).await;
+    });
+    *PLAYBACK.write() = Some(Playback { name, task });
+}
+
+/// Stops the running macro, if any, and releases what it was holding.
+///
+/// Cheap when nothing is playing, so it can run on every touch.
+pub fn abort() {
+    if PLAYBACK.peek().is_none() {
+        return;
+    }
+    let Some(playback) = PLAYBACK.write().take() else {
+        return;
+    };
+    playback.task.cancel();
+    // Taken now, since a macro played right after would start filling it again
+    spawn_forever(release(take_held()));
+}
+
+// --- COMPONENT: Header recorder and macro launcher ---
+#[component]
+pub fn MacroBar() -> Element {
+    use_hook(|| {
+        spawn(async {
+            if let Some(saved) = storage::load(STORAGE_KEY).await {
+                *MACROS.write() = saved;
+            }
+        })
+    });
+
+    let recording = RECORDING.read().as_ref().map(|x| x.steps.len());
+    let playing = PLAYBACK.read().as_ref().map(|x| x.name.clone());
+
+    rsx! {
+        div { class: "flex items-center gap-2 overflow-x-auto",
+            button {
+                class: format!("px-4 py-2 rounded-full text-sm font-bold glass-panel transition-all {}",
+                    if recording.is_some() { "bg-red-500/40 text-white" } else { "text-white/60 hover:bg-white/20" }),
+                onclick: move |_| {
+                    if RECORDING.peek().is_some() {
+                        stop_recording();
+                    } else {
+                        start_recording();
+                    }
+                },
+                if let Some(steps) = recording { "■ {steps}" } else { "● Rec" }
+            }
+
+            for (i, macro_) in MACROS().into_iter().enumerate() {
+                button {
+                    key: "{i}",
+                    class: format!("px-4 py-2 rounded-full text-sm font-bold glass-panel whitespace-nowrap transition-all {}",
+                        if playing.as_ref() == Some(&macro_.name) { "bg-pink-500/40 text-white" } else { "text-white/60 hover:bg-white/20" }),
+                    disabled: recording.is_some(),
+                    onclick: move |_| play(macro_.clone()),
+                    "▶ {macro_.name}"
+                }
+            }
+        }
+    }
+}
+
+// --- COMPONENT: Macro management for the Settings tab ---
+#[component]
+pub fn MacroList() -> Element {
+    rsx! {
+        div { class: "glass-border-gradient !rounded-3xl w-full",
+            div { class: "glass-panel p-8 rounded-3xl flex flex-col gap-6",
+                div {
+                    h3 { class: "text-xl font-bold text-white", "Macros" }
+                    p { class: "text-sm text-white/40", "Record with ● Rec in the header, replay from there too" }
+                }
+
+                if MACROS.read().is_empty() {
+                    p { class: "text-sm text-white/40 text-center", "Nothing recorded yet" }
+                }
+
+                for (i, macro_) in MACROS().into_iter().enumerate() {
+                    div { key: "{i}", class: "flex items-center gap-4",
+                        input {
+                            class: "flex-1 bg-transparent border-b border-white/20 focus:border-pink-500 outline-none py-1 font-bold",
+                            value: "{macro_.name}",
+                            onchange: move |evt| {
+                                MACROS.write()[i].name = evt.value();
+                                save_macros();
+                            },
+                        }
+                        span { class: "text-sm text-white/40 font-mono",
+                            "{macro_.steps.len()} steps, {macro_.duration().as_secs_f64():.1}s"
+                        }
+                        button {
+                            class: "px-3 py-1 rounded-full text-sm font-bold text-white/40 hover:text-red-400 transition-all",
+                            onclick: move |_| {
+                                MACROS.write().remove(i);
+                                save_macros();
+                            },
+                            "✕"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(command: Command) -> Step {
+        Step {
+            offset_ms: 0,
+            command,
+        }
+    }
+
+    #[test]
+    fn held_levers_are_recorded_once_and_centered() {
+        let lever = Command::new("pull_up", Status::Value(60));
+        let steps = [step(lever.clone()), step(Command::pressed("go_front"))];
+        assert!(is_repeat(&steps, &lever));
+        assert!(!is_repeat(
+            &steps,
+            &Command::new("pull_up", Status::Value(80))
+        ));
+        let blink = Command::new("led", Status::BlinkOnce);
+        assert!(!is_repeat(&[step(blink.clone())], &blink));
+        assert_eq!(
+            lever.release(),
+            Some(Command::new("pull_up", Status::Value(0)))
+        );
+        assert_eq!(
+            Command::new("blink_rate", Status::Value(60)).release(),
+            None
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Hand-written command sequences, e.g. `arm_up 500ms; go_front 2s; blink_rate 50`.
+//!
+//! Statements are separated by `;` or newlines and `#` starts a comment. Each statement is
+//! one of:
+//!
+//! - `<action> <duration>` holds a momentary action, like `go_front 2s`
+//! - `<setting> <value>` changes a setting, like `blink_rate 50`
+//! - `wait <duration>` does nothing for a while
+//!
+//! Durations are written in `ms` or `s` and run back to back. Scripts are compiled into a
+//! [`Macro`] so they're played, aborted and released exactly like recorded ones.
+
+use std::{fmt::Display, time::Duration};
+
+use dioxus::prelude::*;
+
+use crate::{
+    capabilities::SettingLimits,
+    command::{Command, MOMENTARY_ACTIONS, SETTINGS, Setting},
+    recorder::{self, Macro, Step},
+};
+
+/// Keeps a typo like `go_front 20s` (meant `2s`) from driving off for a minute.
+const MAX_HOLD: Duration = Duration::from_secs(30);
+/// Keeps a macro from sitting in the middle of a run for ages, still holding its toggles.
+const MAX_WAIT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Statement {
+    Hold {
+        action: &'static str,
+        duration: Duration,
+    },
+    Set {
+        setting: Setting,
+        value: i32,
+    },
+    Wait(Duration),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    /// 1-based, like an editor shows it.
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+enum Arg {
+    Duration(Duration),
+    Value(i32),
+}
+
+pub fn parse(src: &str) -> Result<Vec<Statement>, ParseError> {
+    let error = |token: &str, message: String| {
+        let before = &src[..token.as_ptr() as usize - src.as_ptr() as usize];
+        let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message,
+        }
+    };
+
+    let statements = src
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|code| code.split(';'));
+
+    let mut parsed = Vec::new();
+    for statement in statements {
+        let mut tokens = statement.split_whitespace();
+        let Some(name) = tokens.next() else {
+            continue;
+        };
+        let arg = tokens.next();
+        parsed.push(statement_for(name, arg).map_err(|(token, message)| error(token, message))?);
+
+        if let Some(extra) = tokens.next() {
+            return Err(error(
+                extra,
+                format!("unexpected `{extra}`; separate statements with `;` or a new line"),
+            ));
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// What `name` takes as its argument, phrased to follow "is missing" or "takes".
+fn expected(name: &str) -> &'static str {
+    if SETTINGS.iter().any(|x| x.action == name) {
+        "a value, like `50`"
+    } else {
+        "a duration, like `500ms` or `2s`"
+    }
+}
+
+fn parse_arg(token: &str) -> Result<Arg, String> {
+    let split = token
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(token.len());
+    let (number, unit) = token.split_at(split);
+    if number.is_empty() {
+        return Err(format!("expected a number, found `{token}`"));
+    }
+
+    let seconds = match unit {
+        "" => {
+            return number
+                .parse()
+                .map(Arg::Value)
+                .map_err(|_| format!("`{number}` isn't a whole number"));
+        }
+        "ms" => 0.001,
+        "s" => 1.0,
+        _ => {
+            return Err(format!(
+                "unknown unit `{unit}`; durations use `ms` or `s`, values have no unit"
+            ));
+        }
+    };
+    let number = number
+        .parse::<f64>()
+        .map_err(|_| format!("`{number}` isn't a number"))?;
+    if number < 0.0 {
+        return Err(format!("`{token}` is negative"));
+    }
+    Duration::try_from_secs_f64(number * seconds)
+        .map(Arg::Duration)
+        .map_err(|_| format!("`{token}` is too long"))
+}
+
+/// Errors carry the token they're about, so they can point at it.
+fn statement_for<'a>(name: &'a str, arg: Option<&'a str>) -> Result<Statement, (&'a str, String)> {
+    let momentary = MOMENTARY_ACTIONS.into_iter().find(|x| *x == name);
+    let setting = SETTINGS.into_iter().find(|x| x.action == name);
+    if name != "wait" && momentary.is_none() && setting.is_none() {
+        let message = match suggestion(name) {
+            Some(x) => format!("unknown command `{name}`; did you mean `{x}`?"),
+            None => format!("unknown command `{name}`"),
+        };
+        return Err((name, message));
+    }
+
+    let Some(arg) = arg else {
+        return Err((name, format!("`{name}` is missing {}", expected(name))));
+    };
+
+    match (parse_arg(arg).map_err(|x| (arg, x))?, momentary, setting) {
+        (Arg::Duration(x), None, None) if x > MAX_WAIT => Err((
+            arg,
+            format!("`wait` lasts at most {}s", MAX_WAIT.as_secs_f64()),
+        )),
+        (Arg::Duration(x), None, None) => Ok(Statement::Wait(x)),
+        (Arg::Duration(x), Some(_), _) if x > MAX_HOLD => Err((
+            arg,
+            format!(
+                "`{name}` can be held for at most {}s",
+                MAX_HOLD.as_secs_f64()
+            ),
+        )),
+        (Arg::Duration(duration), Some(action), _) => Ok(Statement::Hold { action, duration }),
+        (Arg::Value(value), _, Some(setting))
+            if (setting.min..=setting.max).contains(&value)
+                && !SettingLimits::from(setting).is_safe(value) =>
+        {
+            Err((
+                arg,
+                format!("`{name}` {value} is outside the safe range; unlock it in Settings"),
+            ))
+        }
+        (Arg::Value(value), _, Some(setting)) if (setting.min..=setting.max).contains(&value) => {
+            Ok(Statement::Set { setting, value })
+        }
+        (Arg::Value(value), _, Some(setting)) => Err((
+            arg,
+            format!(
+                "`{name}` must be between {} and {}, got {value}",
+                setting.min, setting.max
+            ),
+        )),
+        _ => Err((arg, format!("`{name}` takes {}", expected(name)))),
+    }
+}
+
+/// The known command closest to a misspelled `name`, if it's close enough to be a typo.
+fn suggestion(name: &str) -> Option<&'static str> {
+    let known = MOMENTARY_ACTIONS
+        .into_iter()
+        .chain(SETTINGS.iter().map(|x| x.action))
+        .chain(["wait"]);
+    known
+        .map(|x| (edit_distance(name, x), x))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, x)| x)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Lays the statements out on a timeline, pressing and releasing around every hold.
+pub fn to_macro(name: String, statements: &[Statement]) -> Macro {
+    let mut steps = Vec::new();
+    let mut at = Duration::ZERO;
+    let mut push = |at: Duration, command| {
+        steps.push(Step {
+            offset_ms: at.as_millis() as u64,
+            command,
+        });
+    };
+
+    for statement in statements {
+        match *statement {
+            Statement::Hold { action, duration } => {
+                push(at, Command::pressed(action));
+                at += duration;
+                push(at, Command::released(action));
+            }
+            Statement::Set { setting, value } => {
+                push(at, SettingLimits::from(setting).command(value));
+            }
+            Statement::Wait(duration) => at += duration,
+        }
+    }
+
+    Macro { name, steps }
+}
+
+// --- COMPONENT: Script editor for the Settings tab ---
+#[component]
+pub fn ScriptEditor() -> Element {
+    let mut source = use_signal(|| "arm_up 500ms; go_front 2s; blink_rate 50".to_owned());
+    let parsed = use_memo(move || parse(&source.read()));
+
+    let compiled = move |name: String| parsed.read().as_ref().ok().map(|x| to_macro(name, x));
+
+    rsx! {
+        div { class: "glass-border-gradient !rounded-3xl w-full",
+            div { class: "glass-panel p-8 rounded-3xl flex flex-col gap-6",
+                div {
+                    h3 { class: "text-xl font-bold text-white", "Script" }
+                    p { class: "text-sm text-white/40", "Hold actions for a duration, set values, or wait: `arm_up 500ms; wait 1s; blink_rate 50`" }
+                }
+
+                textarea {
+                    class: "w-full h-32 p-4 rounded-2xl bg-black/30 font-mono text-sm outline-none focus:ring-1 focus:ring-pink-500 select-text",
+                    spellcheck: false,
+                    value: "{source}",
+                    oninput: move |evt| source.set(evt.value()),
+                }
+
+                match &*parsed.read() {
+                    Ok(statements) => rsx! {
+                        p { class: "text-sm text-white/40 font-mono", "{statements.len()} statements" }
+                    },
+                    Err(err) => rsx! {
+                        p { class: "text-sm text-red-400 font-mono", "{err}" }
+                    },
+                }
+
+                div { class: "flex justify-end gap-4",
+                    button {
+                        class: "px-6 py-2 rounded-full text-sm font-bold glass-panel hover:bg-white/20 transition-all disabled:opacity-40",
+                        disabled: parsed.read().is_err(),
+                        onclick: move |_| {
+                            if let Some(macro_) = compiled(format!("Script {}", recorder::MACROS.peek().len() + 1)) {
+                                recorder::add(macro_);
+                            }
+                        },
+                        "Save as macro"
+                    }
+                    button {
+                        class: "px-6 py-2 rounded-full text-sm font-bold bg-pink-500/40 hover:bg-pink-500/60 transition-all disabled:opacity-40",
+                        disabled: parsed.read().is_err(),
+                        onclick: move |_| {
+                            if let Some(macro_) = compiled("Script".to_owned()) {
+                                recorder::play(macro_);
+                            }
+                        },
+                        "Run"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_err(src: &str) -> String {
+        parse(src).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parses_example() {
+        let parsed = parse("arm_up 500ms; go_front 2s; blink_rate 50").unwrap();
+        assert_eq!(
+            parsed,
+            [
+                Statement::Hold {
+                    action: "arm_up",
+                    duration: Duration::from_millis(500),
+                },
+                Statement::Hold {
+                    action: "go_front",
+                    duration: Duration::from_secs(2),
+                },
+                Statement::Set {
+                    setting: crate::command::BLINK_RATE,
+                    value: 50,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn newlines_comments_and_empty_statements() {
+        let parsed = parse("# warm up\nwait 1.5s;;\n\narm_down 250ms; # lower\n").unwrap();
+        assert_eq!(
+            parsed,
+            [
+                Statement::Wait(Duration::from_millis(1500)),
+                Statement::Hold {
+                    action: "arm_down",
+                    duration: Duration::from_millis(250),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_token() {
+        assert_eq!(
+            parse_err("go_front 1s;\narm_up 2x"),
+            "2:8: unknown unit `x`; durations use `ms` or `s`, values have no unit"
+        );
+        assert_eq!(
+            parse_err("go_front 1s extra"),
+            "1:13: unexpected `extra`; separate statements with `;` or a new line"
+        );
+        assert_eq!(
+            parse_err("wait 1s; go_front"),
+            "1:10: `go_front` is missing a duration, like `500ms` or `2s`"
+        );
+    }
+
+    #[test]
+    fn suggests_close_commands() {
+        assert_eq!(
+            parse_err("arm_upp 1s"),
+            "1:1: unknown command `arm_upp`; did you mean `arm_up`?"
+        );
+        assert_eq!(parse_err("dance 1s"), "1:1: unknown command `dance`");
+    }
+
+    #[test]
+    fn arguments_must_match_the_command() {
+        assert_eq!(
+            parse_err("go_front 50"),
+            "1:10: `go_front` takes a duration, like `500ms` or `2s`"
+        );
+        assert_eq!(
+            parse_err("blink_rate 50ms"),
+            "1:12: `blink_rate` takes a value, like `50`"
+        );
+        assert_eq!(
+            parse_err("wait 3"),
+            "1:6: `wait` takes a duration, like `500ms` or `2s`"
+        );
+    }
+
+    #[test]
+    fn validates_ranges() {
+        assert_eq!(
+            parse_err("pwm_percentage 120"),
+            "1:16: `pwm_percentage` must be between 20 and 100, got 120"
+        );
+        assert_eq!(
+            parse_err("pwm_percentage 100"),
+            "1:16: `pwm_percentage` 100 is outside the safe range; unlock it in Settings"
+        );
+        assert_eq!(parse_err("go_back -1s"), "1:9: `-1s` is negative");
+        assert_eq!(
+            parse_err("go_back 31s"),
+            "1:9: `go_back` can be held for at most 30s"
+        );
+    }
+
+    #[test]
+    fn huge_durations_are_errors() {
+        assert_eq!(
+            parse_err("wait 99999999999999999999s"),
+            "1:6: `99999999999999999999s` is too long"
+        );
+        assert_eq!(
+            parse_err("go_front 99999999999999999999s"),
+            "1:10: `99999999999999999999s` is too long"
+        );
+        assert!(parse("go_front 1e30s").is_err());
+        assert_eq!(parse_err("wait 301s"), "1:6: `wait` lasts at most 300s");
+        assert!(parse("wait 300s").is_ok());
+    }
+
+    #[test]
+    fn every_hold_is_released() {
+        let parsed = parse("arm_up 500ms; wait 1s; go_front 2s; blink_rate 50").unwrap();
+        let steps = to_macro("test".to_owned(), &parsed)
+            .steps
+            .into_iter()
+            .map(|x| (x.offset_ms, x.command.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            [
+                (0, "cmd=arm_up&status=pressed".to_owned()),
+                (500, "cmd=arm_up&status=released".to_owned()),
+                (1500, "cmd=go_front&status=pressed".to_owned()),
+                (3500, "cmd=go_front&status=released".to_owned()),
+                (3500, "cmd=blink_rate&status=50".to_owned()),
+            ]
+        );
+    }
+}
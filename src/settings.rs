@@ -0,0 +1,423 @@
+//! What the robot's settings are set to, with undo/redo, saving and resetting.
+//!
+//! Every change made from a slider is recorded so an accidental drag can be taken back; a drag
+//! counts as one change. Values can be saved per robot and reverted to later, or reset to the
+//! defaults. Each of these sends the commands bringing the robot along, and so does selecting a
+//! robot this device may drive: it's sent its saved values, so the sliders never show values
+//! it wasn't given. A device that spectates leaves the driver's values alone.
+//!
+//! Values outside their setting's safe range are only sent once unlocked: [`admit`] turns them
+//! away on their way to the robot otherwise, whether a slider, script, macro or layout sent them.
+//! Meanwhile a setting goes as far as is safe and the value waits in [`pending`]. Each unlock
+//! lets one value through.
+
+use std::{collections::HashMap, time::Duration};
+
+use dioxus::prelude::*;
+
+use crate::{
+    ROBOT, auth,
+    capabilities::{self, SettingLimits},
+    command::{Command, SETTINGS, try_send},
+    governor, lease, storage,
+    time::Instant,
+};
+
+/// Changes to one setting closer together than this are undone together, like a drag.
+const MERGE_WITHIN: Duration = Duration::from_secs(1);
+
+#[derive(Clone, PartialEq, Debug)]
+struct Change {
+    limits: SettingLimits,
+    from: i32,
+    to: i32,
+}
+
+/// Changes undone or redone together.
+type Step = Vec<Change>;
+
+#[derive(Default)]
+struct History {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    last_change: Option<Instant>,
+}
+
+impl History {
+    /// Records `change`, folding it into the last step if it continues the same drag.
+    fn record(&mut self, change: Change) {
+        self.redo.clear();
+        let now = Instant::now();
+        let recent = self
+            .last_change
+            .replace(now)
+            .is_some_and(|x| now.duration_since(x) < MERGE_WITHIN);
+        if recent
+            && let Some([last]) = self.undo.last_mut().map(Vec::as_mut_slice)
+            && last.limits.action == change.limits.action
+        {
+            last.to = change.to;
+            return;
+        }
+        self.undo.push(vec![change]);
+    }
+
+    /// Records `step` on its own, never folded into a drag that follows it.
+    fn push(&mut self, step: Step) {
+        self.redo.clear();
+        self.undo.push(step);
+        self.last_change = None;
+    }
+
+    /// The last step, to be taken back, moving it to be redone.
+    fn undo(&mut self) -> Option<Step> {
+        let step = self.undo.pop()?;
+        self.redo.push(step.clone());
+        self.last_change = None;
+        Some(step)
+    }
+
+    /// The last step undone, to be made again, moving it back to be undone.
+    fn redo(&mut self) -> Option<Step> {
+        let step = self.redo.pop()?;
+        self.undo.push(step.clone());
+        self.last_change = None;
+        Some(step)
+    }
+}
+
+/// The changes setting each of `values`, leaving out those already at `current`'s value.
+fn changes(
+    values: impl IntoIterator<Item = (SettingLimits, i32)>,
+    current: impl Fn(&SettingLimits) -> i32,
+) -> Step {
+    values
+        .into_iter()
+        .map(|(limits, to)| Change {
+            from: current(&limits),
+            limits,
+            to,
+        })
+        .filter(|x| x.from != x.to)
+        .collect()
+}
+
+/// Values set since the robot was selected, by action. Settings missing are at their default.
+static VALUES: GlobalSignal<HashMap<String, i32>> = Signal::global(HashMap::new);
+/// Values saved for the selected robot, by action.
+static SAVED: GlobalSignal<HashMap<String, i32>> = Signal::global(HashMap::new);
+/// Why the robot didn't take the last value sent, by action.
+static ERRORS: GlobalSignal<HashMap<String, String>> = Signal::global(HashMap::new);
+static HISTORY: GlobalSignal<History> = Signal::global(History::default);
+/// Values outside the safe range waiting to be unlocked, by action.
+static PENDING: GlobalSignal<HashMap<String, i32>> = Signal::global(HashMap::new);
+/// Unsafe commands unlocked to be sent once.
+static UNLOCKED: GlobalSignal<Vec<Command>> = Signal::global(Vec::new);
+/// The robot whose saved values are in [`SAVED`].
+static LOADED: GlobalSignal<Option<String>> = Signal::global(|| None);
+
+fn storage_key() -> String {
+    format!("settings/{}", ROBOT.peek().name)
+}
+
+pub fn value(limits: &SettingLimits) -> i32 {
+    VALUES
+        .read()
+        .get(&limits.action)
+        .copied()
+        .unwrap_or(limits.default)
+}
+
+/// The saved value, or the default if none was saved.
+pub fn saved(limits: &SettingLimits) -> i32 {
+    SAVED
+        .read()
+        .get(&limits.action)
+        .copied()
+        .unwrap_or(limits.default)
+}
+
+pub fn error(action: &str) -> Option<String> {
+    ERRORS.read().get(action).cloned()
+}
+
+/// Shows why a value for `action` wasn't sent.
+pub fn refuse(action: &str, reason: String) {
+    ERRORS.write().insert(action.to_owned(), reason);
+}
+
+/// The unsafe value waiting to be unlocked for `action`, if any.
+pub fn pending(action: &str) -> Option<i32> {
+    PENDING.read().get(action).copied()
+}
+
+/// Sends the value waiting for `limits`' setting.
+pub fn unlock(limits: &SettingLimits) {
+    let Some(value) = PENDING.write().remove(&limits.action) else {
+        return;
+    };
+    UNLOCKED.write().push(limits.command(value));
+    send(limits, value);
+}
+
+/// Drops the value waiting for `action`.
+pub fn cancel(action: &str) {
+    PENDING.write().remove(action);
+}
+
+/// The value to send for `value`, and the one left waiting to be unlocked if it's unsafe.
+fn gate(limits: &SettingLimits, value: i32, unlocked: bool) -> (i32, Option<i32>) {
+    if limits.is_safe(value) || unlocked {
+        (value, None)
+    } else {
+        // Meanwhile go as far as is safe
+        (limits.clamp_safe(value), Some(value))
+    }
+}
+
+fn send(limits: &SettingLimits, value: i32) {
+    let unlocked = UNLOCKED.peek().contains(&limits.command(value));
+    let (value, waiting) = gate(limits, value, unlocked);
+    match waiting {
+        Some(x) => PENDING.write().insert(limits.action.clone(), x),
+        None => PENDING.write().remove(&limits.action),
+    };
+    if waiting.is_some() && VALUES.peek().get(&limits.action) == Some(&value) {
+        return;
+    }
+    VALUES.write().insert(limits.action.clone(), value);
+    ERRORS.write().remove(&limits.action);
+    let command = limits.command(value);
+    let (action, shown) = (
+        limits.action.clone(),
+        limits.unit.format(value, limits.unit),
+    );
+    spawn(async move {
+        if let Err(err) = try_send(command).await {
+            refuse(&action, format!("The robot refused {shown}: {err}"));
+        }
+    });
+}
+
+/// Refuses `command` if it sets a value outside its setting's safe range that wasn't unlocked,
+/// taking the unlock if it was.
+pub fn admit(command: &Command) -> Result<(), String> {
+    let Some(setting) = SETTINGS.into_iter().find(|x| x.action == command.action) else {
+        return Ok(());
+    };
+    if capabilities::limits(setting).is_safe_status(command.status) {
+        return Ok(());
+    }
+    let mut unlocked = UNLOCKED.write();
+    let Some(i) = unlocked.iter().position(|x| x == command) else {
+        return Err(format!(
+            "{} is outside the safe range of `{}`; unlock it in Settings",
+            command.status, command.action
+        ));
+    };
+    unlocked.remove(i);
+    Ok(())
+}
+
+/// Sets and sends `value`, recording it to be undone.
+pub fn set(limits: &SettingLimits, value: i32) {
+    if let Err(err) = limits.validate(value) {
+        refuse(&limits.action, err);
+        return;
+    }
+    let from = self::value(limits);
+    send(limits, value);
+    HISTORY.write().record(Change {
+        limits: limits.clone(),
+        from,
+        to: value,
+    });
+}
+
+/// Sets and sends several values as one step, skipping those already set.
+pub fn set_all(values: impl IntoIterator<Item = (SettingLimits, i32)>) {
+    let step = changes(values, value);
+    if step.is_empty() {
+        return;
+    }
+    for change in &step {
+        send(&change.limits, change.to);
+    }
+    HISTORY.write().push(step);
+}
+
+pub fn revert(limits: &[SettingLimits]) {
+    set_all(limits.iter().map(|x| (x.clone(), saved(x))));
+}
+
+pub fn reset(limits: &[SettingLimits]) {
+    set_all(limits.iter().map(|x| (x.clone(), x.default)));
+}
+
+/// Saves the current values of `limits` for the selected robot.
+pub fn save(limits: &[SettingLimits]) {
+    let mut saved = SAVED.write();
+    for x in limits {
+        saved.insert(x.action.clone(), value(x));
+    }
+    storage::save(&storage_key(), &*saved);
+}
+
+pub fn can_undo() -> bool {
+    !HISTORY.read().undo.is_empty()
+}
+
+pub fn can_redo() -> bool {
+    !HISTORY.read().redo.is_empty()
+}
+
+pub fn undo() {
+    let Some(step) = HISTORY.write().undo() else {
+        return;
+    };
+    for change in step.iter().rev() {
+        send(&change.limits, change.from);
+    }
+}
+
+pub fn redo() {
+    let Some(step) = HISTORY.write().redo() else {
+        return;
+    };
+    for change in &step {
+        send(&change.limits, change.to);
+    }
+}
+
+/// Starts over with the selected robot's saved values whenever the robot changes.
+pub fn use_settings() {
+    use_effect(|| {
+        let robot = ROBOT.read().name.clone();
+        VALUES.write().clear();
+        ERRORS.write().clear();
+        PENDING.write().clear();
+        UNLOCKED.write().clear();
+        *HISTORY.write() = History::default();
+        spawn(async move {
+            *SAVED.write() = storage::load(&storage_key()).await.unwrap_or_default();
+            *LOADED.write() = Some(robot);
+        });
+    });
+    use_effect(|| {
+        // `auth::loaded` subscribes the effect to robot changes. Values are only sent once it's
+        // known whether another device drives, and in the unit the robot takes.
+        let ready = auth::loaded()
+            && governor::loaded()
+            && capabilities::loaded()
+            && lease::known()
+            && LOADED.read().as_ref() == Some(&ROBOT.peek().name);
+        if !ready {
+            return;
+        }
+        // Read in the task, so the effect doesn't rerun on every save
+        spawn(async {
+            if !lease::may_drive() {
+                return;
+            }
+            for limits in SETTINGS.map(capabilities::limits) {
+                send(&limits, saved(&limits));
+            }
+        });
+    });
+}
+
+// --- COMPONENT: Undo, save and reset for the Settings tab ---
+#[component]
+pub fn SettingsToolbar(settings: Vec<SettingLimits>) -> Element {
+    let modified = settings.iter().any(|x| value(x) != saved(x));
+    let defaults = settings.iter().all(|x| value(x) == x.default);
+    let button = "px-4 py-2 rounded-full text-sm font-bold glass-panel hover:bg-white/20 transition-all disabled:opacity-30";
+    let (revert_all, reset_all, save_all) = (settings.clone(), settings.clone(), settings);
+
+    rsx! {
+        div { class: "flex flex-wrap justify-center gap-2",
+            button { class: button, disabled: !can_undo(), onclick: move |_| undo(), "↶ Undo" }
+            button { class: button, disabled: !can_redo(), onclick: move |_| redo(), "↷ Redo" }
+            button {
+                class: button,
+                disabled: !modified,
+                title: "Send the values saved for this robot",
+                onclick: move |_| revert(&revert_all),
+                "Revert to saved"
+            }
+            button {
+                class: button,
+                disabled: defaults,
+                onclick: move |_| reset(&reset_all),
+                "Reset to defaults"
+            }
+            button {
+                class: "{button} text-pink-400",
+                disabled: !modified,
+                title: "Remember these values for this robot",
+                onclick: move |_| save(&save_all),
+                "Save"
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{BLINK_RATE, PWM_PERCENTAGE};
+
+    fn change(to: i32) -> Change {
+        Change {
+            limits: PWM_PERCENTAGE.into(),
+            from: 100,
+            to,
+        }
+    }
+
+    #[test]
+    fn a_drag_is_undone_at_once() {
+        let mut history = History::default();
+        history.record(change(90));
+        history.record(change(60));
+        assert_eq!(history.undo.len(), 1);
+        assert_eq!(history.undo[0][0].to, 60);
+        history.last_change = None;
+        history.record(change(40));
+        assert_eq!(history.undo.len(), 2);
+    }
+
+    #[test]
+    fn undo_and_redo_move_steps_across() {
+        let mut history = History::default();
+        history.push(vec![change(60)]);
+        history.push(vec![change(40)]);
+        assert_eq!(history.undo().unwrap()[0].to, 40);
+        assert_eq!(history.undo().unwrap()[0].to, 60);
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo().unwrap()[0].to, 60);
+        // A new change can't be redone past
+        history.record(change(80));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo.len(), 2);
+    }
+
+    #[test]
+    fn setting_all_skips_values_already_set() {
+        let blink = SettingLimits::from(BLINK_RATE);
+        let pwm = SettingLimits::from(PWM_PERCENTAGE);
+        let step = changes([(blink.clone(), 100), (pwm, 50)], |x| {
+            if *x == blink { 100 } else { 90 }
+        });
+        assert_eq!(step.len(), 1);
+        assert_eq!((step[0].from, step[0].to), (90, 50));
+    }
+
+    #[test]
+    fn unsafe_values_wait_for_an_unlock() {
+        let limits = SettingLimits::from(PWM_PERCENTAGE);
+        assert_eq!(gate(&limits, 80, false), (80, None));
+        assert_eq!(gate(&limits, 100, false), (90, Some(100)));
+        assert_eq!(gate(&limits, 100, true), (100, None));
+    }
+}
@@ -0,0 +1,39 @@
+//! Persistence through the webview's `localStorage`, which every renderer provides.
+
+use dioxus::prelude::*;
+use serde::{Serialize, de::DeserializeOwned};
+
+pub async fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let eval = document::eval("return localStorage.getItem(await dioxus.recv());");
+    eval.send(key).ok()?;
+    let raw = eval.join::<Option<String>>().await.ok()??;
+    serde_json::from_str(&raw)
+        .inspect_err(|err| warn!("Discarding saved `{key}`: {err}"))
+        .ok()
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    let json = match serde_json::to_string(value) {
+        Ok(x) => x,
+        Err(err) => {
+            error!("Failed to save `{key}`: {err}");
+            return;
+        }
+    };
+    let eval = document::eval(
+        r"
+            const [key, value] = await dioxus.recv();
+            localStorage.setItem(key, value);
+        ",
+    );
+    if let Err(err) = eval.send((key, json)) {
+        error!("Failed to save `{key}`: {err}");
+    }
+}
+
+pub fn remove(key: &str) {
+    let eval = document::eval("localStorage.removeItem(await dioxus.recv());");
+    if let Err(err) = eval.send(key) {
+        error!("Failed to remove `{key}`: {err}");
+    }
+}
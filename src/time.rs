@@ -0,0 +1,12 @@
+//! Clocks and timers that work on every renderer, including the browser where `std::time` panics.
+
+use std::time::Duration;
+
+pub use web_time::Instant;
+
+pub async fn sleep(duration: Duration) {
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(duration).await;
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+}
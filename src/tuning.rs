@@ -0,0 +1,247 @@
+//! How each stick responds to a drag, tunable per control and remembered on this device.
+
+use std::collections::HashMap;
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "stick_tuning";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    Linear,
+    Quadratic,
+    Expo,
+}
+
+impl ResponseCurve {
+    const ALL: [Self; 3] = [Self::Linear, Self::Quadratic, Self::Expo];
+
+    /// Maps a deflection in `0.0..=1.0` onto the output, keeping both ends fixed.
+    pub fn apply(self, x: f64) -> f64 {
+        match self {
+            Self::Linear => x,
+            Self::Quadratic => x * x,
+            Self::Expo => (3.0 * x).exp_m1() / 3.0f64.exp_m1(),
+        }
+    }
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::Quadratic => "Quadratic",
+            Self::Expo => "Expo",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StickTuning {
+    /// How far the puck travels from the center, in pixels.
+    pub max_radius: f64,
+    /// Travel below which the stick counts as centered, in pixels.
+    pub deadzone: f64,
+    /// Multiplier from finger movement to puck travel.
+    pub sensitivity: f64,
+    pub curve: ResponseCurve,
+}
+
+impl StickTuning {
+    pub const fn new(max_radius: f64, deadzone: f64) -> Self {
+        Self {
+            max_radius,
+            deadzone,
+            sensitivity: 1.0,
+            curve: ResponseCurve::Linear,
+        }
+    }
+
+    /// Puck travel for a drag of `distance` pixels, limited to [`Self::max_radius`].
+    pub fn travel(&self, distance: f64) -> f64 {
+        (distance * self.sensitivity).min(self.max_radius)
+    }
+
+    /// How hard the stick is pushed for a drag of `distance` pixels: 0 inside the deadzone up
+    /// to 1 at full travel, shaped by the response curve.
+    pub fn output(&self, distance: f64) -> f64 {
+        let travel = self.travel(distance);
+        if travel < self.deadzone {
+            return 0.0;
+        }
+        let span = (self.max_radius - self.deadzone).max(f64::EPSILON);
+        self.curve.apply(((travel - self.deadzone) / span).min(1.0))
+    }
+}
+
+/// Tunings the user changed, by control title. Controls missing here use their defaults.
+static TUNINGS: GlobalSignal<HashMap<String, StickTuning>> = Signal::global(HashMap::new);
+/// Every mounted stick with its default tuning, so Settings can list them.
+static CONTROLS: GlobalSignal<Vec<(String, StickTuning)>> = Signal::global(Vec::new);
+
+/// The tuning for the stick titled `title`, falling back to `default` until the user changes it.
+pub fn use_tuning(title: String, default: StickTuning) -> Memo<StickTuning> {
+    use_hook({
+        let title = title.clone();
+        move || {
+            if !CONTROLS.peek().iter().any(|(x, _)| *x == title) {
+                CONTROLS.write().push((title, default));
+            }
+        }
+    });
+    use_memo(move || TUNINGS.read().get(&title).copied().unwrap_or(default))
+}
+
+fn save_tunings() {
+    storage::save(STORAGE_KEY, &*TUNINGS.peek());
+}
+
+// --- COMPONENT: Stick tuning for the Settings tab ---
+#[component]
+pub fn TuningPanel() -> Element {
+    use_hook(|| {
+        spawn(async {
+            if let Some(saved) = storage::load(STORAGE_KEY).await {
+                *TUNINGS.write() = saved;
+            }
+        })
+    });
+
+    rsx! {
+        for (title, default) in CONTROLS() {
+            StickTuningCard { key: "{title}", title, default }
+        }
+    }
+}
+
+#[component]
+fn StickTuningCard(title: String, default: StickTuning) -> Element {
+    let tuning = use_memo({
+        let title = title.clone();
+        move || TUNINGS.read().get(&title).copied().unwrap_or(default)
+    });
+    let update = {
+        let title = title.clone();
+        move |change: &dyn Fn(&mut StickTuning)| {
+            let mut tunings = TUNINGS.write();
+            change(tunings.entry(title.clone()).or_insert(default));
+        }
+    };
+
+    rsx! {
+        div { class: "glass-border-gradient !rounded-3xl w-full",
+            div { class: "glass-panel p-8 rounded-3xl flex flex-col gap-6",
+                div { class: "flex justify-between items-end",
+                    div {
+                        h3 { class: "text-xl font-bold text-white", "{title} Stick" }
+                        p { class: "text-sm text-white/40", "Tune how far and how hard a drag pushes the stick" }
+                    }
+                    button {
+                        class: "px-4 py-2 rounded-full text-sm font-bold text-white/40 hover:text-white/80 transition-all",
+                        onclick: {
+                            let title = title.clone();
+                            move |_| {
+                                TUNINGS.write().remove(&title);
+                                save_tunings();
+                            }
+                        },
+                        "Reset"
+                    }
+                }
+
+                TuningSlider {
+                    label: "Radius",
+                    unit: "px",
+                    value: tuning().max_radius,
+                    min: 30.0,
+                    max: 100.0,
+                    step: 5.0,
+                    oninput: {
+                        let update = update.clone();
+                        move |x| update(&|t| t.max_radius = x)
+                    },
+                }
+                TuningSlider {
+                    label: "Deadzone",
+                    unit: "px",
+                    value: tuning().deadzone,
+                    min: 0.0,
+                    max: tuning().max_radius / 2.0,
+                    step: 1.0,
+                    oninput: {
+                        let update = update.clone();
+                        move |x| update(&|t| t.deadzone = x)
+                    },
+                }
+                TuningSlider {
+                    label: "Sensitivity",
+                    unit: "×",
+                    value: tuning().sensitivity,
+                    min: 0.5,
+                    max: 2.0,
+                    step: 0.1,
+                    oninput: {
+                        let update = update.clone();
+                        move |x| update(&|t| t.sensitivity = x)
+                    },
+                }
+
+                div { class: "flex justify-between items-center",
+                    span { class: "text-sm font-bold text-white/60", "Response" }
+                    div { class: "glass-panel flex p-1 rounded-full",
+                        for curve in ResponseCurve::ALL {
+                            button {
+                                class: format!("px-4 py-1 rounded-full text-sm font-bold transition-all {}",
+                                    if tuning().curve == curve { "bg-white/20 text-white shadow-inner" } else { "text-white/40 hover:text-white/60" }),
+                                onclick: {
+                                    let update = update.clone();
+                                    move |_| {
+                                        update(&|t| t.curve = curve);
+                                        save_tunings();
+                                    }
+                                },
+                                "{curve.label()}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn TuningSlider(
+    label: &'static str,
+    unit: &'static str,
+    value: f64,
+    min: f64,
+    max: f64,
+    step: f64,
+    oninput: EventHandler<f64>,
+) -> Element {
+    rsx! {
+        div { class: "flex flex-col gap-2",
+            div { class: "flex justify-between text-sm font-bold",
+                span { class: "text-white/60", "{label}" }
+                span { class: "font-mono text-pink-500", "{value:.1}{unit}" }
+            }
+            input {
+                r#type: "range",
+                min,
+                max,
+                step,
+                value: "{value}",
+                class: "w-full h-2 bg-slate-700/50 rounded-lg appearance-none cursor-pointer accent-pink-500 hover:accent-pink-400 transition-all",
+                oninput: move |evt| {
+                    if let Ok(val) = evt.value().parse::<f64>() {
+                        oninput.call(val);
+                    }
+                },
+                // Dragging fires a flood of input events; only the final value is worth persisting
+                onchange: move |_| save_tunings(),
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Units settings are kept, shown and sent in.
+//!
+//! A setting's value is a whole number in its own [`Unit`], e.g. 8000 Hz. The firmware may
+//! expect another unit of the same quantity, so values are converted with [`to_wire`] on the way
+//! out. Sliders can show a value in any unit of its quantity.
+//!
+//! Action names don't always say the unit sent: `frequency_kilohertz` has always taken Hz, and
+//! firmware in the field relies on that. Robots that take kHz say so in their capabilities.
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::command::Status;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Unit {
+    #[serde(rename = "Hz")]
+    Hertz,
+    #[serde(rename = "kHz")]
+    Kilohertz,
+    #[serde(rename = "ms")]
+    Milliseconds,
+    #[serde(rename = "s")]
+    Seconds,
+    #[serde(rename = "%")]
+    Percent,
+    /// A bare number, like the sliders of custom layouts, or a unit this UI doesn't know.
+    #[default]
+    #[serde(rename = "", other)]
+    Plain,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Quantity {
+    Number,
+    Frequency,
+    Duration,
+    Ratio,
+}
+
+impl Unit {
+    pub const ALL: [Self; 6] = [
+        Self::Plain,
+        Self::Hertz,
+        Self::Kilohertz,
+        Self::Milliseconds,
+        Self::Seconds,
+        Self::Percent,
+    ];
+
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::Plain => "",
+            Self::Hertz => "Hz",
+            Self::Kilohertz => "kHz",
+            Self::Milliseconds => "ms",
+            Self::Seconds => "s",
+            Self::Percent => "%",
+        }
+    }
+
+    pub const fn quantity(self) -> Quantity {
+        match self {
+            Self::Plain => Quantity::Number,
+            Self::Hertz | Self::Kilohertz => Quantity::Frequency,
+            Self::Milliseconds | Self::Seconds => Quantity::Duration,
+            Self::Percent => Quantity::Ratio,
+        }
+    }
+
+    /// How many of the quantity's smallest unit make one of this.
+    const fn scale(self) -> f64 {
+        match self {
+            Self::Kilohertz | Self::Seconds => 1000.0,
+            _ => 1.0,
+        }
+    }
+
+    /// The units a value in this one can also be shown in, this one included.
+    pub fn alternatives(self) -> impl Iterator<Item = Self> {
+        Self::ALL
+            .into_iter()
+            .filter(move |x| x.quantity() == self.quantity())
+    }
+
+    /// `value` in `to`, or `None` if `to` measures something else.
+    pub fn convert(self, value: f64, to: Self) -> Option<f64> {
+        (self.quantity() == to.quantity()).then(|| value * self.scale() / to.scale())
+    }
+
+    /// `value` shown in `to`, e.g. `8kHz` for 8000 Hz.
+    pub fn format(self, value: i32, to: Self) -> String {
+        match self.convert(f64::from(value), to) {
+            Some(x) => format!("{}{to}", Decimal::new(x)),
+            None => format!("{value}{self}"),
+        }
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// A number with up to three decimals, for values that aren't whole in the firmware's unit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Decimal {
+    thousandths: i64,
+}
+
+impl Decimal {
+    pub fn new(value: f64) -> Self {
+        Self {
+            thousandths: (value * 1000.0).round() as i64,
+        }
+    }
+
+    pub fn get(self) -> f64 {
+        self.thousandths as f64 / 1000.0
+    }
+
+    /// The value, if it's a whole number that fits.
+    pub fn whole(self) -> Option<i32> {
+        if self.thousandths % 1000 == 0 {
+            i32::try_from(self.thousandths / 1000).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.thousandths < 0 { "-" } else { "" };
+        let (whole, fraction) = (
+            self.thousandths.unsigned_abs() / 1000,
+            self.thousandths.unsigned_abs() % 1000,
+        );
+        if fraction == 0 {
+            write!(f, "{sign}{whole}")
+        } else {
+            let fraction = format!("{fraction:03}");
+            write!(f, "{sign}{whole}.{}", fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse::<f64>() {
+            Ok(x) if x.is_finite() => Ok(Self::new(x)),
+            _ => Err(format!("`{s}` isn't a number")),
+        }
+    }
+}
+
+/// `value` in `unit` as the status for a firmware taking `wire`. Bare numbers, and units that
+/// can't be converted, are sent as they are.
+pub fn to_wire(value: i32, unit: Unit, wire: Unit) -> Status {
+    if unit == Unit::Plain || wire == Unit::Plain {
+        return Status::Value(value);
+    }
+    match unit.convert(f64::from(value), wire).map(Decimal::new) {
+        Some(x) => x.whole().map_or(Status::Decimal(x), Status::Value),
+        None => Status::Value(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{SETTINGS, Setting};
+
+    #[test]
+    fn converts_within_a_quantity_only() {
+        assert_eq!(Unit::Hertz.convert(8000.0, Unit::Kilohertz), Some(8.0));
+        assert_eq!(Unit::Seconds.convert(1.5, Unit::Milliseconds), Some(1500.0));
+        assert_eq!(Unit::Hertz.convert(1.0, Unit::Milliseconds), None);
+        assert_eq!(Unit::Hertz.format(500, Unit::Kilohertz), "0.5kHz");
+    }
+
+    #[test]
+    fn sends_fractions_only_when_needed() {
+        assert_eq!(
+            to_wire(8000, Unit::Hertz, Unit::Kilohertz),
+            Status::Value(8)
+        );
+        assert_eq!(
+            to_wire(500, Unit::Hertz, Unit::Kilohertz).to_string(),
+            "0.5"
+        );
+        assert_eq!(
+            to_wire(75, Unit::Milliseconds, Unit::Milliseconds),
+            Status::Value(75)
+        );
+        assert_eq!(to_wire(75, Unit::Plain, Unit::Percent), Status::Value(75));
+    }
+
+    #[test]
+    fn parses_symbols_and_tolerates_unknown_units() {
+        assert_eq!(
+            serde_json::from_str::<Unit>(r#""kHz""#).unwrap(),
+            Unit::Kilohertz
+        );
+        assert_eq!(
+            serde_json::from_str::<Unit>(r#""rpm""#).unwrap(),
+            Unit::Plain
+        );
+        assert_eq!(
+            "0.5".parse::<Status>(),
+            Ok(Status::Decimal(Decimal::new(0.5)))
+        );
+    }
+
+    /// Actions whose name says another unit than the firmware takes, kept for compatibility.
+    const MISNAMED: [&str; 1] = ["frequency_kilohertz"];
+
+    /// How `unit` is spelled at the end of action names, e.g. `pwm_percentage`.
+    const fn name(unit: Unit) -> Option<&'static str> {
+        match unit {
+            Unit::Plain => None,
+            Unit::Hertz => Some("hertz"),
+            Unit::Kilohertz => Some("kilohertz"),
+            Unit::Milliseconds => Some("milliseconds"),
+            Unit::Seconds => Some("seconds"),
+            Unit::Percent => Some("percentage"),
+        }
+    }
+
+    /// The unit `action`'s name says it takes, if it says.
+    fn named_by(action: &str) -> Option<Unit> {
+        Unit::ALL.into_iter().find(|&unit| {
+            name(unit)
+                .is_some_and(|name| action.strip_suffix(name).is_some_and(|x| x.ends_with('_')))
+        })
+    }
+
+    #[test]
+    fn reads_units_from_action_names() {
+        assert_eq!(named_by("frequency_kilohertz"), Some(Unit::Kilohertz));
+        assert_eq!(named_by("frequency_hertz"), Some(Unit::Hertz));
+        assert_eq!(named_by("pwm_percentage"), Some(Unit::Percent));
+        assert_eq!(named_by("blink_rate"), None);
+    }
+
+    #[test]
+    fn bundled_settings_send_the_unit_their_action_names() {
+        for Setting {
+            action, unit, wire, ..
+        } in SETTINGS
+        {
+            assert_eq!(unit.quantity(), wire.quantity(), "{action}");
+            match named_by(action) {
+                Some(named) if MISNAMED.contains(&action) => assert_ne!(wire, named, "{action}"),
+                Some(named) => assert_eq!(wire, named, "{action}"),
+                None => {}
+            }
+        }
+    }
+}
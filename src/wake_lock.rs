@@ -0,0 +1,82 @@
+//! Keeps the screen on while driving, through the Screen Wake Lock API.
+//!
+//! The browser drops the lock whenever the page is hidden, so it's requested again each time
+//! the page becomes visible while still wanted.
+
+use dioxus::prelude::*;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockState {
+    /// This browser or webview can't keep the screen on.
+    Unsupported,
+    Released,
+    Held,
+}
+
+static STATE: GlobalSignal<LockState> = Signal::global(|| LockState::Released);
+
+const SCRIPT: &str = r"
+    let sentinel = null;
+    let wanted = false;
+    const report = () => dioxus.send(
+        !('wakeLock' in navigator) ? 'unsupported'
+            : sentinel && !sentinel.released ? 'held' : 'released'
+    );
+    const acquire = async () => {
+        if (wanted && 'wakeLock' in navigator && document.visibilityState === 'visible'
+            && !(sentinel && !sentinel.released)) {
+            try {
+                sentinel = await navigator.wakeLock.request('screen');
+                sentinel.addEventListener('release', report);
+            } catch (err) {
+                // Denied, e.g. by battery saver; stay released
+            }
+        }
+        report();
+    };
+    document.addEventListener('visibilitychange', acquire);
+    while (true) {
+        wanted = await dioxus.recv();
+        if (wanted) {
+            await acquire();
+        } else {
+            await sentinel?.release();
+            sentinel = null;
+            report();
+        }
+    }
+";
+
+/// Holds the wake lock whenever `wanted` is true.
+pub fn use_wake_lock(wanted: Memo<bool>) {
+    let eval = use_hook(|| {
+        let mut eval = document::eval(SCRIPT);
+        spawn(async move {
+            while let Ok(state) = eval.recv().await {
+                *STATE.write() = state;
+            }
+        });
+        eval
+    });
+    use_effect(move || {
+        if let Err(err) = eval.send(wanted()) {
+            warn!("Failed to update the wake lock: {err}");
+        }
+    });
+}
+
+// --- COMPONENT: Wake lock indicator for the header ---
+#[component]
+pub fn WakeLockIndicator() -> Element {
+    let (class, hint) = match STATE() {
+        LockState::Held => ("text-yellow-300", "Screen kept on"),
+        LockState::Released => ("text-white/30", "Screen may sleep"),
+        LockState::Unsupported => ("text-white/10", "This device can't keep the screen on"),
+    };
+
+    rsx! {
+        span { class: "p-3 rounded-xl glass-panel font-bold text-lg {class}", title: hint, "☀" }
+    }
+}